@@ -0,0 +1,65 @@
+// STD Dependencies -----------------------------------------------------------
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+// External Dependencies ------------------------------------------------------
+use cursive::traits::*;
+use cursive::views::Dialog;
+
+// Modules --------------------------------------------------------------------
+use cursive_tree_view::{AsyncLazyTree, ItemHandle, Placement, TreeView};
+
+// Example --------------------------------------------------------------------
+fn main() {
+    // Forwards the load requests to the worker thread
+    struct Remote {
+        requests: Sender<(String, ItemHandle)>,
+    }
+
+    impl AsyncLazyTree<String> for Remote {
+        fn placeholder(&mut self, _: &String) -> String {
+            "Loading…".to_string()
+        }
+
+        fn request_children(&mut self, item: &String, handle: ItemHandle) {
+            self.requests.send((item.clone(), handle)).ok();
+        }
+    }
+
+    let mut siv = cursive::default();
+    let (requests, receiver) = mpsc::channel::<(String, ItemHandle)>();
+
+    // Simulates a slow source and delivers the children back to the tree
+    let sink = siv.cb_sink().clone();
+    thread::spawn(move || {
+        for (item, handle) in receiver {
+            thread::sleep(Duration::from_secs(1));
+
+            let children: Vec<(String, bool)> = (1..=3)
+                .map(|i| (format!("{}.{}", item, i), i < 3))
+                .collect();
+
+            let delivered = sink.send(Box::new(move |siv| {
+                siv.call_on_name("tree", |tree: &mut TreeView<String>| {
+                    tree.replace_children(&handle, children)
+                });
+            }));
+            if delivered.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut tree = TreeView::<String>::new().async_loader(Remote { requests });
+    for i in 1..=3 {
+        tree.insert_container_item(i.to_string(), Placement::After, i - 1);
+    }
+
+    siv.add_layer(
+        Dialog::around(tree.with_name("tree").scrollable().min_size((40, 15)))
+            .title("Async Loading"),
+    );
+
+    siv.run();
+}
@@ -1,7 +1,3 @@
-// Crate Dependencies ---------------------------------------------------------
-use cursive;
-
-
 // External Dependencies ------------------------------------------------------
 use cursive::direction::Orientation;
 use cursive::traits::*;
@@ -146,7 +142,7 @@ c - Clear all items.
         let value = siv.call_on_name("tree", move |tree: &mut TreeView<String>| {
             tree.borrow_item(row)
                 .map(|s| s.to_string())
-                .unwrap_or_else(|| "".to_string())
+                .unwrap_or_default()
         });
 
         siv.call_on_name("status", move |view: &mut TextView| {
@@ -1,7 +1,3 @@
-// Crate Dependencies ---------------------------------------------------------
-use cursive;
-
-
 // STD Dependencies -----------------------------------------------------------
 use std::cmp::Ordering;
 use std::env;
@@ -9,21 +5,26 @@ use std::fmt;
 use std::fs;
 use std::io;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 // External Dependencies ------------------------------------------------------
+use cursive::theme::{Effect, Style};
 use cursive::traits::*;
-use cursive::views::Dialog;
-use cursive::Cursive;
+use cursive::views::{Dialog, LinearLayout, TextView};
 
 // Modules --------------------------------------------------------------------
-use cursive_tree_view::{Placement, TreeView};
+use cursive_tree_view::{LazyTree, Placement, TreeView};
 
 // Example --------------------------------------------------------------------
 fn main() {
     #[derive(Debug)]
     struct TreeEntry {
         name: String,
-        dir: Option<PathBuf>,
+        path: PathBuf,
+        is_dir: bool,
+        size: u64,
+        modified: Option<SystemTime>,
     }
 
     impl fmt::Display for TreeEntry {
@@ -32,27 +33,69 @@ fn main() {
         }
     }
 
-    fn collect_entries(dir: &PathBuf, entries: &mut Vec<TreeEntry>) -> io::Result<()> {
+    // Order of the entries within each directory
+    #[derive(Debug, Copy, Clone)]
+    enum FileSort {
+        NameAsc,
+        NameDesc,
+        MtimeDesc,
+        SizeDesc,
+    }
+
+    impl FileSort {
+        fn next(self) -> Self {
+            match self {
+                FileSort::NameAsc => FileSort::NameDesc,
+                FileSort::NameDesc => FileSort::MtimeDesc,
+                FileSort::MtimeDesc => FileSort::SizeDesc,
+                FileSort::SizeDesc => FileSort::NameAsc,
+            }
+        }
+
+        fn compare(self, a: &TreeEntry, b: &TreeEntry) -> Ordering {
+            let by_name = a.name.to_lowercase().cmp(&b.name.to_lowercase());
+            match self {
+                FileSort::NameAsc => by_name,
+                FileSort::NameDesc => by_name.reverse(),
+                FileSort::MtimeDesc => b.modified.cmp(&a.modified).then(by_name),
+                FileSort::SizeDesc => b.size.cmp(&a.size).then(by_name),
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    struct Settings {
+        show_hidden: bool,
+        dir_suffix: bool,
+        sort: FileSort,
+    }
+
+    fn collect_entries(
+        dir: &PathBuf,
+        entries: &mut Vec<TreeEntry>,
+        show_hidden: bool,
+    ) -> io::Result<()> {
         if dir.is_dir() {
             for entry in fs::read_dir(dir)? {
                 let entry = entry?;
                 let path = entry.path();
 
-                if path.is_dir() {
-                    entries.push(TreeEntry {
-                        name: entry
-                            .file_name()
-                            .into_string()
-                            .unwrap_or_else(|_| "".to_string()),
-                        dir: Some(path),
-                    });
-                } else if path.is_file() {
+                // Skip dotfiles unless hidden files are shown
+                if !show_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
+
+                if path.is_dir() || path.is_file() {
+                    let metadata = fs::metadata(&path).ok();
                     entries.push(TreeEntry {
                         name: entry
                             .file_name()
                             .into_string()
                             .unwrap_or_else(|_| "".to_string()),
-                        dir: None,
+                        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                        modified: metadata.and_then(|m| m.modified().ok()),
+                        is_dir: path.is_dir(),
+                        path,
                     });
                 }
             }
@@ -60,54 +103,211 @@ fn main() {
         Ok(())
     }
 
-    fn expand_tree(tree: &mut TreeView<TreeEntry>, parent_row: usize, dir: &PathBuf) {
+    fn sorted_entries(dir: &PathBuf, settings: Settings) -> Vec<(TreeEntry, bool)> {
         let mut entries = Vec::new();
-        collect_entries(dir, &mut entries).ok();
+        collect_entries(dir, &mut entries, settings.show_hidden).ok();
 
-        entries.sort_by(|a, b| match (a.dir.is_some(), b.dir.is_some()) {
-            (true, true) | (false, false) => a.name.cmp(&b.name),
+        // Directories are always listed before files
+        entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+            (true, true) | (false, false) => settings.sort.compare(a, b),
             (true, false) => Ordering::Less,
             (false, true) => Ordering::Greater,
         });
 
-        for i in entries {
-            if i.dir.is_some() {
-                tree.insert_container_item(i, Placement::LastChild, parent_row);
+        entries
+            .into_iter()
+            .map(|entry| {
+                let is_dir = entry.is_dir;
+                (entry, is_dir)
+            })
+            .collect()
+    }
+
+    // Lazily loads directory listings for sub nodes
+    struct DirLoader {
+        settings: Arc<Mutex<Settings>>,
+    }
+
+    impl LazyTree<TreeEntry> for DirLoader {
+        fn load_children(&mut self, item: &TreeEntry) -> Vec<(TreeEntry, bool)> {
+            let settings = *self.settings.lock().unwrap();
+            sorted_entries(&item.path, settings)
+        }
+    }
+
+    // Replaces all items with the listing of the top level directory, so
+    // sub directories are loaded again once they are expanded
+    fn load_tree(tree: &mut TreeView<TreeEntry>, path: &PathBuf, settings: Settings) {
+        tree.clear();
+        tree.insert_item(
+            TreeEntry {
+                name: path.file_name().unwrap().to_str().unwrap().to_string(),
+                path: path.clone(),
+                is_dir: true,
+                size: 0,
+                modified: None,
+            },
+            Placement::After,
+            0,
+        );
+
+        for (entry, is_dir) in sorted_entries(path, settings) {
+            if is_dir {
+                tree.insert_container_item(entry, Placement::LastChild, 0);
+            } else {
+                tree.insert_item(entry, Placement::LastChild, 0);
+            }
+        }
+    }
+
+    // Reloads the children of the directory at `row`, or of the parent
+    // directory in case of a file, keeping the focus on the entry of the same
+    // name if it still exists
+    fn refresh(tree: &mut TreeView<TreeEntry>, row: usize, settings: Settings) {
+        let dir = match tree.borrow_item(row) {
+            Some(entry) if entry.is_dir => row,
+            Some(_) => match tree.item_parent(row) {
+                Some(parent) => parent,
+                None => return,
+            },
+            None => return,
+        };
+
+        let focused = tree
+            .row()
+            .and_then(|row| tree.borrow_item(row))
+            .map(|entry| entry.path.clone());
+
+        // Collapsed directories are loaded again once they are expanded
+        tree.remove_children(dir);
+        if tree.is_collapsed(dir) != Some(false) {
+            return;
+        }
+
+        let path = tree.borrow_item(dir).unwrap().path.clone();
+        for (entry, is_dir) in sorted_entries(&path, settings) {
+            if is_dir {
+                tree.insert_container_item(entry, Placement::LastChild, dir);
             } else {
-                tree.insert_item(i, Placement::LastChild, parent_row);
+                tree.insert_item(entry, Placement::LastChild, dir);
+            }
+        }
+
+        if let Some(focused) = focused {
+            let rows = tree.find_rows(|entry| entry.path == focused);
+            if let Some(&row) = rows.first() {
+                tree.set_selected_row(row);
             }
         }
     }
 
+    // Appends a slash to the names of directories in case `suffix` is set
+    fn set_dir_suffix(tree: &mut TreeView<TreeEntry>, suffix: bool) {
+        tree.set_item_renderer(move |entry: &TreeEntry| {
+            if suffix && entry.is_dir {
+                format!("{}/", entry.name).into()
+            } else {
+                entry.name.clone().into()
+            }
+        });
+    }
+
+    // Hidden files are not shown by default
+    let settings = Arc::new(Mutex::new(Settings {
+        show_hidden: false,
+        dir_suffix: true,
+        sort: FileSort::NameAsc,
+    }));
+
     // Create TreeView with initial working directory
-    let mut tree = TreeView::<TreeEntry>::new();
+    let mut tree = TreeView::<TreeEntry>::new()
+        .lazy_loader(DirLoader {
+            settings: settings.clone(),
+        })
+        .item_style_callback(|entry: &TreeEntry, _, _| {
+            // Show directories in bold
+            if entry.is_dir {
+                Style::from(Effect::Bold)
+            } else {
+                Style::default()
+            }
+        })
+        .on_select(|siv, row| {
+            // Show the path of the selected entry in the preview pane
+            let text = siv
+                .call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+                    tree.borrow_item(row).map(|entry| {
+                        if entry.is_dir {
+                            format!("{}", entry.path.display())
+                        } else {
+                            format!("{}\n\n{} bytes", entry.path.display(), entry.size)
+                        }
+                    })
+                })
+                .flatten()
+                .unwrap_or_default();
+
+            siv.call_on_name("preview", |view: &mut TextView| view.set_content(text));
+        });
     let path = env::current_dir().expect("Working directory missing.");
 
-    tree.insert_item(
-        TreeEntry {
-            name: path.file_name().unwrap().to_str().unwrap().to_string(),
-            dir: Some(path.clone()),
-        },
-        Placement::After,
-        0,
+    load_tree(&mut tree, &path, *settings.lock().unwrap());
+    set_dir_suffix(&mut tree, settings.lock().unwrap().dir_suffix);
+
+    // Setup Cursive
+    let mut siv = cursive::default();
+    siv.add_layer(
+        Dialog::around(
+            LinearLayout::horizontal()
+                .child(tree.with_name("tree").scrollable())
+                .child(TextView::empty().with_name("preview").min_width(30)),
+        )
+        .title("File View (. hidden files, / slashes, s order, r refresh)"),
     );
 
-    expand_tree(&mut tree, 0, &path);
+    // Toggle the slashes without reloading the listings
+    let suffix_settings = settings.clone();
+    siv.add_global_callback('/', move |siv| {
+        let suffix = {
+            let mut settings = suffix_settings.lock().unwrap();
+            settings.dir_suffix = !settings.dir_suffix;
+            settings.dir_suffix
+        };
+        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            set_dir_suffix(tree, suffix)
+        });
+    });
 
-    // Lazily insert directory listings for sub nodes
-    tree.set_on_collapse(|siv: &mut Cursive, row, is_collapsed, children| {
-        if !is_collapsed && children == 0 {
-            siv.call_on_name("tree", move |tree: &mut TreeView<TreeEntry>| {
-                if let Some(dir) = tree.borrow_item(row).unwrap().dir.clone() {
-                    expand_tree(tree, row, &dir);
-                }
-            });
-        }
+    // Reload the focused directory
+    let refresh_settings = settings.clone();
+    siv.add_global_callback('r', move |siv| {
+        let settings = *refresh_settings.lock().unwrap();
+        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            if let Some(row) = tree.row() {
+                refresh(tree, row, settings);
+            }
+        });
     });
 
-    // Setup Cursive
-    let mut siv = cursive::default();
-    siv.add_layer(Dialog::around(tree.with_name("tree").scrollable()).title("File View"));
+    // Change the settings and reload the listings
+    let reload = move |siv: &mut cursive::Cursive, change: fn(&mut Settings)| {
+        let settings = {
+            let mut settings = settings.lock().unwrap();
+            change(&mut settings);
+            *settings
+        };
+        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            load_tree(tree, &path, settings)
+        });
+    };
+
+    let toggle_hidden = reload.clone();
+    siv.add_global_callback('.', move |siv| {
+        toggle_hidden(siv, |settings| settings.show_hidden = !settings.show_hidden)
+    });
+    siv.add_global_callback('s', move |siv| {
+        reload(siv, |settings| settings.sort = settings.sort.next())
+    });
 
     siv.run();
 }
@@ -14,14 +14,20 @@ extern crate cursive_core as cursive;
 extern crate debug_stub_derive;
 
 // STD Dependencies -----------------------------------------------------------
-use std::cmp;
+use std::cmp::{self, Ordering};
 use std::fmt::{Debug, Display};
+use std::iter;
+use std::mem;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 // External Dependencies ------------------------------------------------------
-use cursive::direction::Direction;
+use cursive::direction::{Absolute, Direction};
 use cursive::event::{Callback, Event, EventResult, Key, MouseButton, MouseEvent};
-use cursive::theme::ColorStyle;
+use cursive::theme::{ColorStyle, Style};
+use cursive::utils::lines::simple::{make_lines, simple_prefix, simple_suffix};
+use cursive::utils::lines::spans::LinesIterator;
+use cursive::utils::markup::StyledString;
 use cursive::vec::Vec2;
 use cursive::view::{CannotFocus, View};
 use cursive::{Cursive, Printer};
@@ -29,8 +35,8 @@ use cursive::{Rect, With};
 
 // Internal Dependencies ------------------------------------------------------
 mod tree_list;
-pub use tree_list::Placement;
-use tree_list::TreeList;
+pub use tree_list::{CheckState, ExtractedTree, ItemHandle, Placement, RetainPolicy, TreeBuilder};
+use tree_list::{TreeList, TreeNode};
 
 /// Callback taking an item index as input.
 type IndexCallback = Arc<dyn Fn(&mut Cursive, usize) + Send + Sync>;
@@ -38,6 +44,119 @@ type IndexCallback = Arc<dyn Fn(&mut Cursive, usize) + Send + Sync>;
 /// Callback taking as input the row ID, the collapsed state, and the child ID.
 type CollapseCallback = Arc<dyn Fn(&mut Cursive, usize, bool, usize) + Send + Sync>;
 
+/// Callback taking as input the row ID, the future collapsed state, and the
+/// child ID, returning whether the change is allowed.
+type PreCollapseCallback = Arc<dyn Fn(usize, bool, usize) -> bool + Send + Sync>;
+
+/// Callback taking as input the row ID and the child ID.
+type ExpandCallback = Arc<dyn Fn(&mut Cursive, usize, usize) + Send + Sync>;
+
+/// Callback taking as input the row ID and the new checked state.
+type CheckCallback = Arc<dyn Fn(&mut Cursive, usize, bool) + Send + Sync>;
+
+/// Callback taking as input the edge of the tree which was reached.
+type EdgeCallback = Arc<dyn Fn(&mut Cursive, Edge) + Send + Sync>;
+
+/// Callback taking as input the tree and the row ID, returning whether the
+/// row may be expanded.
+type BeforeExpandCallback<T> = Arc<dyn Fn(&mut TreeView<T>, usize) -> bool + Send + Sync>;
+
+/// Callback taking as input an item, its row ID and whether it is part of the
+/// selection, returning the style to draw it with.
+type StyleCallback<T> = Arc<dyn Fn(&T, usize, bool) -> Style + Send + Sync>;
+
+/// Callback taking an item as input, returning the styled text to draw it as.
+type RenderCallback<T> = Arc<dyn Fn(&T) -> StyledString + Send + Sync>;
+
+/// Shared loader populating the children of expanded containers.
+type LazyLoader<T> = Arc<Mutex<dyn LazyTree<T> + Send>>;
+type AsyncLoader<T> = Arc<Mutex<dyn AsyncLazyTree<T> + Send>>;
+
+/// Callback taking the row ID as input, returning whether the row may be left.
+type LeaveCallback = Arc<dyn Fn(usize) -> bool + Send + Sync>;
+
+/// Callback taking an item as input, returning the callback to run with it.
+type ItemCallback<T> = Arc<dyn Fn(&T) -> Callback + Send + Sync>;
+
+/// Callback taking as input an item, the collapsed state, and the child ID,
+/// returning the callback to run with them.
+type ItemCollapseCallback<T> = Arc<dyn Fn(&T, bool, usize) -> Callback + Send + Sync>;
+
+/// Determines how items which do not fit into the available width are
+/// shortened, see [`TreeView::set_truncation`](struct.TreeView.html#method.set_truncation).
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Truncate {
+    /// Items are clipped at the edge of the view.
+    None,
+
+    /// The end of the item is replaced with an ellipsis.
+    End,
+
+    /// The middle of the item is replaced with an ellipsis, keeping both its
+    /// start and end visible.
+    Middle,
+}
+
+/// An edge of a [`TreeView`](struct.TreeView.html) past which the user tried
+/// to move the focus, see
+/// [`TreeView::set_on_edge`](struct.TreeView.html#method.set_on_edge).
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Edge {
+    /// `<Up>` was pressed on the first row.
+    Top,
+
+    /// `<Down>` was pressed on the last row.
+    Bottom,
+}
+
+/// Determines how typed characters are matched against items, see
+/// [`TreeView::set_typeahead`](struct.TreeView.html#method.set_typeahead).
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum TypeaheadMode {
+    /// Typed characters are not searched for.
+    Off,
+
+    /// Items match in case their text starts with the typed characters.
+    Prefix,
+
+    /// Items match in case their text contains the typed characters.
+    Substring,
+}
+
+/// Loads the children of container items on demand, see
+/// [`TreeView::set_lazy_loader`](struct.TreeView.html#method.set_lazy_loader).
+///
+/// Closures taking an item and returning its children implement this trait
+/// as well.
+pub trait LazyTree<T> {
+    /// Returns the children of the container `item` which is about to be
+    /// expanded, as pairs of values and whether they are containers
+    /// themselves.
+    fn load_children(&mut self, item: &T) -> Vec<(T, bool)>;
+}
+
+impl<T, F: FnMut(&T) -> Vec<(T, bool)>> LazyTree<T> for F {
+    fn load_children(&mut self, item: &T) -> Vec<(T, bool)> {
+        self(item)
+    }
+}
+
+/// Loads the children of container items in the background, see
+/// [`TreeView::set_async_loader`](struct.TreeView.html#method.set_async_loader).
+pub trait AsyncLazyTree<T> {
+    /// Returns the value of the placeholder row which is shown as the only
+    /// child of the container `item` until its children arrive.
+    fn placeholder(&mut self, item: &T) -> T;
+
+    /// Starts loading the children of the container `item` which is about to
+    /// be expanded.
+    ///
+    /// Once loaded, the children should be passed to
+    /// [`TreeView::replace_children`](struct.TreeView.html#method.replace_children)
+    /// along with `handle`, e.g. via `Cursive::cb_sink`.
+    fn request_children(&mut self, item: &T, handle: ItemHandle);
+}
+
 /// A low level tree view.
 ///
 /// Each view provides a number of low level methods for manipulating its
@@ -63,6 +182,47 @@ type CollapseCallback = Arc<dyn Fn(&mut Cursive, usize, bool, usize) + Send + Sy
 /// tree.insert_item("3".to_string(), Placement::LastChild, 2);
 /// # }
 /// ```
+///
+/// # Scrolling
+///
+/// The view always requests the full height of its visible rows and does not
+/// scroll on its own. Wrap it in a
+/// [`ScrollView`](../cursive/views/struct.ScrollView.html) instead, which
+/// keeps the selected row in view via `important_area` and provides the
+/// scrollbar, including mouse dragging:
+///
+/// ```rust
+/// # extern crate cursive;
+/// # extern crate cursive_tree_view;
+/// # use cursive::view::Scrollable;
+/// # use cursive_tree_view::TreeView;
+/// # fn main() {
+/// let scroll = TreeView::<String>::new().scrollable();
+/// # }
+/// ```
+///
+/// # Borrowed Items
+///
+/// Cursive requires views to be `'static`, so only trees owning their items
+/// can be added to a layout. The same goes for the callbacks receiving items
+/// like [`set_on_submit_item`](#method.set_on_submit_item), which move clones
+/// of them into a `Callback`. All other methods are available for borrowed
+/// items as well, e.g. for building and inspecting a `TreeView<&str>`:
+///
+/// ```rust
+/// # extern crate cursive;
+/// # extern crate cursive_tree_view;
+/// # use cursive_tree_view::{Placement, TreeView};
+/// # fn main() {
+/// let path = String::from("usr/share/doc");
+///
+/// let mut tree = TreeView::<&str>::new();
+/// for (row, name) in path.split('/').enumerate() {
+///     tree.insert_item(name, Placement::LastChild, row.saturating_sub(1));
+/// }
+/// assert_eq!(tree.path_string(2, "/"), Some(path.clone()));
+/// # }
+/// ```
 #[derive(DebugStub)]
 pub struct TreeView<T: Display + Debug> {
     enabled: bool,
@@ -70,19 +230,120 @@ pub struct TreeView<T: Display + Debug> {
     #[debug_stub(some = "Arc<Fn(&mut Cursive, usize)")]
     on_submit: Option<IndexCallback>,
 
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, usize)")]
+    on_submit_container: Option<IndexCallback>,
+
     #[debug_stub(some = "Arc<Fn(&mut Cursive, usize)")]
     on_select: Option<IndexCallback>,
 
     #[debug_stub(some = "Arc<Fn(&mut Cursive, usize, bool, usize)>")]
     on_collapse: Option<CollapseCallback>,
 
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, usize, usize)>")]
+    on_expand: Option<ExpandCallback>,
+
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, usize, usize)>")]
+    on_collapse_only: Option<ExpandCallback>,
+
+    #[debug_stub(some = "Arc<Fn(usize, bool, usize) -> bool>")]
+    on_pre_collapse: Option<PreCollapseCallback>,
+
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, usize, bool)>")]
+    on_check: Option<CheckCallback>,
+
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, Edge)>")]
+    on_edge: Option<EdgeCallback>,
+
+    #[debug_stub(some = "Arc<Fn(&mut TreeView<T>, usize) -> bool>")]
+    on_before_expand: Option<BeforeExpandCallback<T>>,
+
+    #[debug_stub(some = "Arc<Mutex<LazyTree<T>>>")]
+    lazy_loader: Option<LazyLoader<T>>,
+
+    #[debug_stub(some = "Arc<Mutex<AsyncLazyTree<T>>>")]
+    async_loader: Option<AsyncLoader<T>>,
+
+    #[debug_stub(some = "Arc<Fn(&T, usize, bool) -> Style>")]
+    item_style: Option<StyleCallback<T>>,
+
+    #[debug_stub(some = "Arc<Fn(&T) -> StyledString>")]
+    renderer: Option<RenderCallback<T>>,
+
+    #[debug_stub(some = "Arc<Fn(usize) -> bool>")]
+    on_leave: Option<LeaveCallback>,
+
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, &T)>")]
+    on_submit_item: Option<ItemCallback<T>>,
+
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, &T)>")]
+    on_select_item: Option<ItemCallback<T>>,
+
+    #[debug_stub(some = "Arc<Fn(&mut Cursive, &T, bool, usize)>")]
+    on_collapse_item: Option<ItemCollapseCallback<T>>,
+
     last_size: Vec2,
     focus: usize,
     list: TreeList<T>,
+
+    wrap: bool,
+    truncation: Truncate,
+    row_offsets: Vec<usize>,
+
+    indent_guides: bool,
+    full_row_highlight: bool,
+    row_color: ColorStyle,
+    alternate_row_color: Option<ColorStyle>,
+    selection_style: ColorStyle,
+    inactive_selection_style: ColorStyle,
+
+    /// Widest item, measured without the checkbox and symbol, along with the
+    /// revision of the list it was measured for
+    max_width: Option<(usize, usize)>,
+
+    /// Guide lines drawn in front of the first and any further lines of
+    /// each visible row
+    guides: Vec<(String, String)>,
+
+    /// Range of lines which were visible during the last draw
+    viewport: Mutex<(usize, usize)>,
+
+    multi_select: bool,
+    checkboxes: bool,
+
+    allow_deselect: bool,
+    deselected: bool,
+
+    /// ID of the selected item as of the last selection change notification
+    notified_id: Option<usize>,
+    notify_programmatic: bool,
+
+    vim_keys: bool,
+    accordion: bool,
+    arrow_keys: bool,
+    wrap_navigation: bool,
+
+    /// Query of the fuzzy filter, if any
+    filter: Option<String>,
+
+    typeahead: TypeaheadMode,
+    typeahead_expand: bool,
+    search_buffer: String,
+    last_search_input: Option<Instant>,
+
+    collapsed_symbol: String,
+    expanded_symbol: String,
+    leaf_symbol: String,
+
+    /// Width of the widest symbol, plus one for a space between the symbol
+    /// and the item
+    symbol_width: usize,
 }
 
-/// One character for the symbol, and one for a space between the sybol and the item
-const SYMBOL_WIDTH: usize = 2;
+/// Width of the checkbox drawn in front of the symbol, including a space.
+const CHECKBOX_WIDTH: usize = 4;
+
+/// Time after which the buffer of the type-to-search is cleared.
+const SEARCH_TIMEOUT: Duration = Duration::from_millis(1000);
 
 impl<T: Display + Debug + Send + Sync> Default for TreeView<T> {
     /// Creates a new, empty `TreeView`.
@@ -96,15 +357,77 @@ impl<T: Display + Debug + Send + Sync> TreeView<T> {
         Self {
             enabled: true,
             on_submit: None,
+            on_submit_container: None,
             on_select: None,
             on_collapse: None,
+            on_expand: None,
+            on_collapse_only: None,
+            on_pre_collapse: None,
+            on_check: None,
+            on_edge: None,
+            on_before_expand: None,
+            lazy_loader: None,
+            async_loader: None,
+            item_style: None,
+            renderer: None,
+            on_leave: None,
+            on_submit_item: None,
+            on_select_item: None,
+            on_collapse_item: None,
 
             last_size: (0, 0).into(),
             focus: 0,
             list: TreeList::new(),
+
+            wrap: false,
+            truncation: Truncate::None,
+            row_offsets: Vec::new(),
+            indent_guides: false,
+            full_row_highlight: true,
+            row_color: ColorStyle::primary(),
+            alternate_row_color: None,
+            selection_style: ColorStyle::highlight(),
+            inactive_selection_style: ColorStyle::highlight_inactive(),
+            max_width: None,
+            guides: Vec::new(),
+
+            viewport: Mutex::new((0, 0)),
+
+            multi_select: false,
+            checkboxes: false,
+
+            allow_deselect: false,
+            deselected: false,
+
+            notified_id: None,
+            notify_programmatic: false,
+
+            vim_keys: false,
+            accordion: false,
+            arrow_keys: true,
+            wrap_navigation: false,
+            filter: None,
+            typeahead: TypeaheadMode::Off,
+            typeahead_expand: false,
+            search_buffer: String::new(),
+            last_search_input: None,
+
+            collapsed_symbol: "▸".to_string(),
+            expanded_symbol: "▾".to_string(),
+            leaf_symbol: "◦".to_string(),
+            symbol_width: 2,
         }
     }
 
+    /// Creates a new, empty `TreeView` which uses the plain ASCII symbols
+    /// `"+"`, `"-"` and `"*"` for terminals or fonts which render the
+    /// default symbols poorly.
+    ///
+    /// See [`set_symbols`](#method.set_symbols) for using other symbols.
+    pub fn ascii() -> Self {
+        Self::new().symbols("+", "-", "*")
+    }
+
     /// Disables this view.
     ///
     /// A disabled view cannot be selected.
@@ -127,488 +450,7156 @@ impl<T: Display + Debug + Send + Sync> TreeView<T> {
         self.enabled
     }
 
-    /// Sets a callback to be used when `<Enter>` is pressed while an item
-    /// is selected.
+    /// Enables or disables wrapping of long items.
+    ///
+    /// When enabled, items which do not fit into the available width are
+    /// wrapped on word boundaries and span multiple lines, with continuation
+    /// lines aligned under the start of the item text.
+    ///
+    /// Rows still refer to whole items, regardless of how many lines they
+    /// occupy on screen. Moving the focus steps over whole items, the
+    /// focused item is highlighted across all of its lines and clicking any
+    /// of its lines selects it.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Enables or disables wrapping of long items.
+    ///
+    /// Chainable variant.
+    pub fn wrap(self, wrap: bool) -> Self {
+        self.with(|t| t.set_wrap(wrap))
+    }
+
+    /// Sets a callback which determines the style each visible item is drawn
+    /// with, including its symbol.
+    ///
+    /// The callback receives the item, its row and whether it is part of the
+    /// selection made in multi select mode. Its style replaces the default
+    /// styling of disabled and selected items, while the focused row keeps
+    /// the highlight color combined with the effects of the returned style,
+    /// so the cursor always stays visible.
     ///
     /// # Example
     ///
     /// ```rust
     /// # extern crate cursive;
     /// # extern crate cursive_tree_view;
-    /// # use cursive::Cursive;
+    /// # use cursive::theme::{BaseColor, Color, Effect, Style};
     /// # use cursive_tree_view::TreeView;
     /// # fn main() {
     /// # let mut tree = TreeView::<String>::new();
-    /// tree.set_on_submit(|siv: &mut Cursive, row: usize| {
-    ///
+    /// tree.set_item_style_callback(|item: &String, row: usize, selected: bool| {
+    ///     if item.starts_with("error") {
+    ///         Style::from(Color::Dark(BaseColor::Red))
+    ///     } else if item.ends_with('/') {
+    ///         Style::from(Effect::Bold)
+    ///     } else {
+    ///         Style::default()
+    ///     }
     /// });
     /// # }
     /// ```
-    pub fn set_on_submit<F>(&mut self, cb: F)
+    pub fn set_item_style_callback<F>(&mut self, cb: F)
     where
-        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+        F: Fn(&T, usize, bool) -> Style + Send + Sync + 'static,
     {
-        self.on_submit = Some(Arc::new(move |s, row| cb(s, row)));
+        self.item_style = Some(Arc::new(cb));
     }
 
-    /// Sets a callback to be used when `<Enter>` is pressed while an item
-    /// is selected.
+    /// Sets a callback which determines the style each visible item is drawn
+    /// with, including its symbol.
     ///
     /// Chainable variant.
+    pub fn item_style_callback<F>(self, cb: F) -> Self
+    where
+        F: Fn(&T, usize, bool) -> Style + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_item_style_callback(cb))
+    }
+
+    /// Sets a callback which turns items into styled text, allowing a single
+    /// item to be drawn with multiple colors and effects, e.g. a file name
+    /// followed by a dimmed size.
+    ///
+    /// The `Display` implementation of the items is still used for searching,
+    /// while widths, wrapping and truncation are based on the styled text.
+    /// The focused row is drawn using the plain text of the styled spans, so
+    /// the highlight cannot be hidden by the colors of individual spans.
     ///
     /// # Example
     ///
     /// ```rust
     /// # extern crate cursive;
     /// # extern crate cursive_tree_view;
-    /// # use cursive::Cursive;
+    /// # use cursive::theme::{BaseColor, Color};
+    /// # use cursive::utils::markup::StyledString;
     /// # use cursive_tree_view::TreeView;
     /// # fn main() {
     /// # let mut tree = TreeView::<String>::new();
-    /// tree.on_submit(|siv: &mut Cursive, row: usize| {
-    ///
+    /// tree.set_item_renderer(|item: &String| {
+    ///     let mut styled = StyledString::plain(item.as_str());
+    ///     styled.append_styled(" (new)", Color::Dark(BaseColor::Green));
+    ///     styled
     /// });
     /// # }
     /// ```
-    pub fn on_submit<F>(self, cb: F) -> Self
+    pub fn set_item_renderer<F>(&mut self, cb: F)
     where
-        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+        F: Fn(&T) -> StyledString + Send + Sync + 'static,
     {
-        self.with(|t| t.set_on_submit(cb))
+        self.renderer = Some(Arc::new(cb));
+        self.max_width = None;
     }
 
-    /// Sets a callback to be used when an item is selected.
+    /// Sets a callback which turns items into styled text.
+    ///
+    /// Chainable variant.
+    pub fn item_renderer<F>(self, cb: F) -> Self
+    where
+        F: Fn(&T) -> StyledString + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_item_renderer(cb))
+    }
+
+    /// Enables or disables drawing guide lines within the indentation of
+    /// items, like the `tree` command does.
+    ///
+    /// When enabled, each item is connected to its parent via `├─` or `└─`,
+    /// depending on whether it is followed by another sibling, while `│`
+    /// continues the lines of parents which are followed by siblings further
+    /// down. The guides are drawn in the secondary color of the theme.
+    /// Defaults to plain indentation.
+    pub fn set_indent_guides(&mut self, indent_guides: bool) {
+        self.indent_guides = indent_guides;
+    }
+
+    /// Enables or disables drawing guide lines within the indentation of
+    /// items, like the `tree` command does.
+    ///
+    /// Chainable variant.
+    pub fn indent_guides(self, indent_guides: bool) -> Self {
+        self.with(|t| t.set_indent_guides(indent_guides))
+    }
+
+    /// Sets whether the highlight of the focused row spans the full width of
+    /// the view, including the indentation in front of the item, like it does
+    /// in a `SelectView`.
+    ///
+    /// When disabled, only the text of the focused item is highlighted.
+    /// Defaults to `true`.
+    pub fn set_full_row_highlight(&mut self, full_row_highlight: bool) {
+        self.full_row_highlight = full_row_highlight;
+    }
+
+    /// Sets whether the highlight of the focused row spans the full width of
+    /// the view.
+    ///
+    /// Chainable variant.
+    pub fn full_row_highlight(self, full_row_highlight: bool) -> Self {
+        self.with(|t| t.set_full_row_highlight(full_row_highlight))
+    }
+
+    /// Sets the colors of the visible rows, alternating between `primary`
+    /// for even and `alternate` for odd rows in case the latter is given.
+    ///
+    /// Striped rows are colored across the full width of the view. Disabled
+    /// and selected items keep their own text color on top of the background
+    /// of their row. Defaults to `ColorStyle::primary()` without striping.
     ///
     /// # Example
     ///
     /// ```rust
     /// # extern crate cursive;
     /// # extern crate cursive_tree_view;
-    /// # use cursive::Cursive;
+    /// # use cursive::theme::{Color, ColorStyle, PaletteColor};
     /// # use cursive_tree_view::TreeView;
     /// # fn main() {
-    /// # let mut tree = TreeView::<String>::new();
-    /// tree.set_on_select(|siv: &mut Cursive, row: usize| {
-    ///
-    /// });
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.set_row_colors(
+    ///     ColorStyle::primary(),
+    ///     Some(ColorStyle::new(PaletteColor::Primary, Color::Rgb(30, 30, 30))),
+    /// );
     /// # }
     /// ```
-    pub fn set_on_select<F>(&mut self, cb: F)
-    where
-        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
-    {
-        self.on_select = Some(Arc::new(move |s, row| cb(s, row)));
+    pub fn set_row_colors(&mut self, primary: ColorStyle, alternate: Option<ColorStyle>) {
+        self.row_color = primary;
+        self.alternate_row_color = alternate;
     }
 
-    /// Sets a callback to be used when an item is selected.
+    /// Sets the colors of the visible rows.
+    ///
+    /// Chainable variant.
+    pub fn row_colors(self, primary: ColorStyle, alternate: Option<ColorStyle>) -> Self {
+        self.with(|t| t.set_row_colors(primary, alternate))
+    }
+
+    /// Sets the color of the focused row while the view has the focus.
+    ///
+    /// Defaults to `ColorStyle::highlight()`.
+    pub fn set_selection_style(&mut self, style: ColorStyle) {
+        self.selection_style = style;
+    }
+
+    /// Sets the color of the focused row while the view has the focus.
+    ///
+    /// Chainable variant.
+    pub fn selection_style(self, style: ColorStyle) -> Self {
+        self.with(|t| t.set_selection_style(style))
+    }
+
+    /// Sets the color of the focused row while the view does not have the
+    /// focus or is disabled.
+    ///
+    /// Defaults to `ColorStyle::highlight_inactive()`.
+    pub fn set_inactive_selection_style(&mut self, style: ColorStyle) {
+        self.inactive_selection_style = style;
+    }
+
+    /// Sets the color of the focused row while the view does not have the
+    /// focus or is disabled.
     ///
     /// Chainable variant.
+    pub fn inactive_selection_style(self, style: ColorStyle) -> Self {
+        self.with(|t| t.set_inactive_selection_style(style))
+    }
+
+    /// Sets how items which do not fit into the available width are
+    /// shortened.
+    ///
+    /// Items are cut on character boundaries according to their display
+    /// width and an ellipsis is appended or inserted. Defaults to
+    /// [`Truncate::None`](enum.Truncate.html#variant.None); has no effect
+    /// while [wrapping](#method.set_wrap) is enabled.
     ///
     /// # Example
     ///
     /// ```rust
     /// # extern crate cursive;
     /// # extern crate cursive_tree_view;
-    /// # use cursive::Cursive;
-    /// # use cursive_tree_view::TreeView;
+    /// # use cursive_tree_view::{Truncate, TreeView};
     /// # fn main() {
-    /// # let mut tree = TreeView::<String>::new();
-    /// tree.on_select(|siv: &mut Cursive, row: usize| {
-    ///
-    /// });
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.set_truncation(Truncate::Middle);
     /// # }
     /// ```
-    pub fn on_select<F>(self, cb: F) -> Self
-    where
-        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
-    {
-        self.with(|t| t.set_on_select(cb))
+    pub fn set_truncation(&mut self, truncation: Truncate) {
+        self.truncation = truncation;
     }
 
-    /// Sets a callback to be used when an item has its children collapsed or expanded.
+    /// Sets how items which do not fit into the available width are
+    /// shortened.
+    ///
+    /// Chainable variant.
+    pub fn truncation(self, truncation: Truncate) -> Self {
+        self.with(|t| t.set_truncation(truncation))
+    }
+
+    /// Enables or disables selection of multiple items.
+    ///
+    /// When enabled, `<Space>` toggles whether the focused item is part of
+    /// the selection. The selection is tracked per item and is therefore kept
+    /// when rows shift due to collapsing or inserting items.
+    pub fn set_multi_select(&mut self, multi_select: bool) {
+        self.multi_select = multi_select;
+    }
+
+    /// Enables or disables selection of multiple items.
+    ///
+    /// Chainable variant.
+    pub fn multi_select(self, multi_select: bool) -> Self {
+        self.with(|t| t.set_multi_select(multi_select))
+    }
+
+    /// Enables or disables a column of checkboxes drawn in front of the
+    /// symbols of all items.
+    ///
+    /// When enabled, `<Space>` checks or unchecks the focused item along with
+    /// all of its descendants, taking precedence over multi select. Items
+    /// with children are shown as checked, partially checked or unchecked
+    /// depending on the state of their descendants.
     ///
     /// # Example
     ///
     /// ```rust
     /// # extern crate cursive;
     /// # extern crate cursive_tree_view;
-    /// # use cursive::Cursive;
-    /// # use cursive_tree_view::TreeView;
+    /// # use cursive_tree_view::{CheckState, Placement, TreeView};
     /// # fn main() {
-    /// # let mut tree = TreeView::<String>::new();
-    /// tree.set_on_collapse(|siv: &mut Cursive, row: usize, is_collapsed: bool, children: usize| {
+    /// let mut tree = TreeView::<String>::new().checkboxes(true);
+    /// tree.insert_item("packages".to_string(), Placement::After, 0);
+    /// tree.insert_item("a".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("b".to_string(), Placement::LastChild, 0);
     ///
-    /// });
+    /// tree.set_checked(1, true);
+    /// assert_eq!(tree.check_state(0), Some(CheckState::Partial));
+    ///
+    /// tree.set_checked(0, true);
+    /// assert_eq!(tree.checked_rows(), vec![0, 1, 2]);
     /// # }
     /// ```
-    pub fn set_on_collapse<F>(&mut self, cb: F)
-    where
-        F: Fn(&mut Cursive, usize, bool, usize) + Send + Sync + 'static,
-    {
-        self.on_collapse = Some(Arc::new(move |s, row, collapsed, children| {
-            cb(s, row, collapsed, children)
-        }));
+    pub fn set_checkboxes(&mut self, checkboxes: bool) {
+        self.checkboxes = checkboxes;
     }
 
-    /// Sets a callback to be used when an item has its children collapsed or expanded.
+    /// Enables or disables a column of checkboxes drawn in front of the
+    /// symbols of all items.
+    ///
+    /// Chainable variant.
+    pub fn checkboxes(self, checkboxes: bool) -> Self {
+        self.with(|t| t.set_checkboxes(checkboxes))
+    }
+
+    /// Enables or disables Vim style `h` and `l` keys.
+    ///
+    /// When enabled, `h` collapses the focused container, or moves the focus
+    /// to the parent of the item in case it is a leaf or already collapsed.
+    /// `l` expands the focused container, or moves the focus to its first
+    /// child in case it is already expanded.
+    ///
+    /// Both keys take precedence over type-to-search, so with both features
+    /// enabled `h` and `l` are never added to the search buffer.
+    pub fn set_vim_keys(&mut self, vim_keys: bool) {
+        self.vim_keys = vim_keys;
+    }
+
+    /// Enables or disables Vim style `h` and `l` keys.
     ///
     /// Chainable variant.
+    pub fn vim_keys(self, vim_keys: bool) -> Self {
+        self.with(|t| t.set_vim_keys(vim_keys))
+    }
+
+    /// Enables or disables accordion mode, in which expanding a container
+    /// via the keyboard or mouse collapses all of its expanded siblings, so
+    /// that only one item per parent is expanded at a time.
+    ///
+    /// The collapse callbacks are invoked for each collapsed sibling before
+    /// they are invoked for the expanded item, while the
+    /// [pre collapse callback](#method.set_on_pre_collapse) is only consulted
+    /// for the expanded item. Items expanded programmatically do not affect
+    /// their siblings.
     ///
     /// # Example
     ///
     /// ```rust
     /// # extern crate cursive;
     /// # extern crate cursive_tree_view;
-    /// # use cursive::Cursive;
-    /// # use cursive_tree_view::TreeView;
+    /// # use cursive::event::{Event, Key};
+    /// # use cursive::view::View;
+    /// # use cursive_tree_view::{Placement, TreeView};
     /// # fn main() {
-    /// # let mut tree = TreeView::<String>::new();
-    /// tree.on_collapse(|siv: &mut Cursive, row: usize, is_collapsed: bool, children: usize| {
+    /// let mut tree = TreeView::<String>::new().accordion(true);
+    /// tree.insert_container_item("a".to_string(), Placement::After, 0);
+    /// tree.insert_item("a1".to_string(), Placement::LastChild, 0);
+    /// tree.insert_container_item("b".to_string(), Placement::After, 0);
+    /// tree.insert_item("b1".to_string(), Placement::LastChild, 1);
     ///
-    /// });
+    /// tree.on_event(Event::Key(Key::Enter));
+    /// tree.on_event(Event::Key(Key::Down));
+    /// tree.on_event(Event::Key(Key::Down));
+    /// tree.on_event(Event::Key(Key::Enter));
+    /// assert_eq!(tree.is_collapsed(0), Some(true));
+    /// assert_eq!(tree.is_collapsed(1), Some(false));
+    /// assert_eq!(tree.row(), Some(1));
     /// # }
     /// ```
-    pub fn on_collapse<F>(self, cb: F) -> Self
-    where
-        F: Fn(&mut Cursive, usize, bool, usize) + Send + Sync + 'static,
-    {
-        self.with(|t| t.set_on_collapse(cb))
+    pub fn set_accordion(&mut self, accordion: bool) {
+        self.accordion = accordion;
     }
 
-    /// Removes all items from this view.
-    pub fn clear(&mut self) {
-        self.list.clear();
-        self.focus = 0;
+    /// Enables or disables accordion mode.
+    ///
+    /// Chainable variant.
+    pub fn accordion(self, accordion: bool) -> Self {
+        self.with(|t| t.set_accordion(accordion))
     }
 
-    /// Removes all items from this view, returning them.
-    pub fn take_items(&mut self) -> Vec<T> {
-        let items = self.list.take_items();
-        self.focus = 0;
-        items
+    /// Enables or disables collapsing and expanding items via the `<Left>`
+    /// and `<Right>` keys.
+    ///
+    /// When enabled, `<Left>` and `<Right>` behave like the Vim style `h` and
+    /// `l` keys, invoking the collapse callbacks just like `<Enter>` does.
+    /// Disable them in case the keys should move the focus between views
+    /// instead, e.g. within a `LinearLayout`. Defaults to `true`.
+    pub fn set_arrow_keys(&mut self, arrow_keys: bool) {
+        self.arrow_keys = arrow_keys;
     }
 
-    /// Returns the number of items in this tree.
-    pub fn len(&self) -> usize {
-        self.list.len()
+    /// Enables or disables collapsing and expanding items via the `<Left>`
+    /// and `<Right>` keys.
+    ///
+    /// Chainable variant.
+    pub fn arrow_keys(self, arrow_keys: bool) -> Self {
+        self.with(|t| t.set_arrow_keys(arrow_keys))
     }
 
-    /// Returns `true` if this tree has no items.
-    pub fn is_empty(&self) -> bool {
-        self.list.is_empty()
+    /// Enables or disables wrapping around when navigating past the first or
+    /// last row.
+    ///
+    /// When enabled, `<Down>` on the last enabled row selects the first one
+    /// and `<Up>` on the first enabled row selects the last one, in place of
+    /// invoking the [edge callback](#method.set_on_edge). `<PageUp>` and
+    /// `<PageDown>` still stop at the first and last row. Defaults to
+    /// `false`.
+    pub fn set_wrap_navigation(&mut self, wrap_navigation: bool) {
+        self.wrap_navigation = wrap_navigation;
     }
 
-    /// Returns the index of the currently selected tree row.
+    /// Enables or disables wrapping around when navigating past the first or
+    /// last row.
     ///
-    /// `None` is returned in case of the tree being empty.
-    pub fn row(&self) -> Option<usize> {
-        if self.is_empty() {
-            None
-        } else {
-            Some(self.focus)
+    /// Chainable variant.
+    pub fn wrap_navigation(self, wrap_navigation: bool) -> Self {
+        self.with(|t| t.set_wrap_navigation(wrap_navigation))
+    }
+
+    /// Sets a query which hides all items not matching it, except for the
+    /// ancestors of matching items which are kept for context.
+    ///
+    /// Items match in case all characters of the query appear in their text
+    /// in the same order, ignoring case, e.g. `"tvw"` matches `"tree_view"`.
+    /// While the filter is active the ancestors of matches are shown
+    /// regardless of their collapsed state and containers cannot be toggled
+    /// by the user. The collapsed state of all items is kept as is, so
+    /// passing `None` restores the tree as it was before filtering.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_container_item("src".to_string(), Placement::After, 0);
+    /// tree.insert_item("tree_view.rs".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("README.md".to_string(), Placement::After, 0);
+    ///
+    /// tree.set_filter(Some("tvw".to_string()));
+    /// assert_eq!(tree.visible_height(), 2);
+    ///
+    /// tree.set_filter(None);
+    /// assert_eq!(tree.visible_height(), 2);
+    /// assert_eq!(tree.is_collapsed(0), Some(true));
+    /// # }
+    /// ```
+    pub fn set_filter(&mut self, query: Option<String>) {
+        self.filter = query;
+        self.apply_filter();
+    }
+
+    /// Sets a query which hides all items not matching it.
+    ///
+    /// Chainable variant.
+    pub fn filter(self, query: Option<String>) -> Self {
+        self.with(|t| t.set_filter(query))
+    }
+
+    /// Enables or disables type-to-search.
+    ///
+    /// When enabled, typed characters are collected into a search buffer and
+    /// the focus moves to the next visible item whose text starts with the
+    /// buffer, ignoring case. The buffer is cleared once no character was
+    /// typed for a second, as well as on any key press or mouse event.
+    ///
+    /// In case multi select is enabled as well, `<Space>` keeps toggling the
+    /// selection instead of being searched for.
+    ///
+    /// This is a shorthand for [`set_typeahead`](#method.set_typeahead) with
+    /// either `TypeaheadMode::Prefix` or `TypeaheadMode::Off`.
+    pub fn set_search_enabled(&mut self, enabled: bool) {
+        self.set_typeahead(if enabled {
+            TypeaheadMode::Prefix
+        } else {
+            TypeaheadMode::Off
+        });
+    }
+
+    /// Enables or disables type-to-search.
+    ///
+    /// Chainable variant.
+    pub fn search_enabled(self, enabled: bool) -> Self {
+        self.with(|t| t.set_search_enabled(enabled))
+    }
+
+    /// Sets how type-to-search matches the typed characters against the
+    /// text of the visible items.
+    ///
+    /// See [`set_search_enabled`](#method.set_search_enabled) for how the
+    /// search buffer is collected. Defaults to `TypeaheadMode::Off`.
+    ///
+    /// While a query is being typed, `*` becomes part of it instead of
+    /// expanding the subtree of the focused item.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::event::Event;
+    /// # use cursive::view::View;
+    /// # use cursive_tree_view::{Placement, TreeView, TypeaheadMode};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new().typeahead(TypeaheadMode::Substring);
+    /// tree.insert_item("Cargo.toml".to_string(), Placement::After, 0);
+    /// tree.insert_item("README.md".to_string(), Placement::After, 0);
+    ///
+    /// tree.on_event(Event::Char('m'));
+    /// tree.on_event(Event::Char('d'));
+    /// assert_eq!(tree.search_query(), "md");
+    /// assert_eq!(tree.row(), Some(1));
+    /// # }
+    /// ```
+    pub fn set_typeahead(&mut self, mode: TypeaheadMode) {
+        self.typeahead = mode;
+        self.search_buffer.clear();
+    }
+
+    /// Sets how type-to-search matches the typed characters.
+    ///
+    /// Chainable variant.
+    pub fn typeahead(self, mode: TypeaheadMode) -> Self {
+        self.with(|t| t.set_typeahead(mode))
+    }
+
+    /// Sets whether type-to-search also finds items hidden within collapsed
+    /// parents, expanding the parents of the next match.
+    ///
+    /// Parents expanded this way do not invoke any collapse callbacks. While
+    /// a [filter](#method.set_filter) is active only the visible items are
+    /// searched. Defaults to `false`.
+    pub fn set_typeahead_expand(&mut self, expand: bool) {
+        self.typeahead_expand = expand;
+    }
+
+    /// Sets whether type-to-search also finds items hidden within collapsed
+    /// parents.
+    ///
+    /// Chainable variant.
+    pub fn typeahead_expand(self, expand: bool) -> Self {
+        self.with(|t| t.set_typeahead_expand(expand))
+    }
+
+    /// Returns the characters typed for the current type-to-search, e.g. for
+    /// showing them in a status line.
+    ///
+    /// The query is empty once it timed out or was cleared by a key press.
+    pub fn search_query(&self) -> &str {
+        match self.last_search_input {
+            Some(last) if last.elapsed() <= SEARCH_TIMEOUT => &self.search_buffer,
+            _ => "",
         }
     }
 
-    /// Returns position on the x axis of the symbol (first character of an item) at the given row.
+    /// Sets the symbols drawn in front of collapsed containers, expanded
+    /// containers and leaf items.
     ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn first_col(&self, row: usize) -> Option<usize> {
-        let index = self.list.row_to_item_index(row);
-        self.list.first_col(index)
+    /// Defaults to `"▸"`, `"▾"` and `"◦"`. The items are aligned after the
+    /// widest of the given symbols.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.set_symbols("+", "-", "*");
+    /// # }
+    /// ```
+    pub fn set_symbols(&mut self, collapsed: &str, expanded: &str, leaf: &str) {
+        self.symbol_width = [collapsed, expanded, leaf]
+            .iter()
+            .map(|symbol| simple_prefix(symbol, usize::MAX).width)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.collapsed_symbol = collapsed.to_string();
+        self.expanded_symbol = expanded.to_string();
+        self.leaf_symbol = leaf.to_string();
     }
 
-    /// Returns total width (including the symbol) of the item at the given row.
+    /// Sets the symbols drawn in front of collapsed containers, expanded
+    /// containers and leaf items.
     ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn item_width(&self, row: usize) -> Option<usize> {
-        let index = self.list.row_to_item_index(row);
-        self.list.width(index).map(|width| width + SYMBOL_WIDTH)
+    /// Chainable variant.
+    pub fn symbols(self, collapsed: &str, expanded: &str, leaf: &str) -> Self {
+        self.with(|t| t.set_symbols(collapsed, expanded, leaf))
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed while an item
+    /// is selected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_submit(|siv: &mut Cursive, row: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_submit<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+    {
+        self.on_submit = Some(Arc::new(move |s, row| cb(s, row)));
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed while an item
+    /// is selected.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.on_submit(|siv: &mut Cursive, row: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn on_submit<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_submit(cb))
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed while a container
+    /// item is selected.
+    ///
+    /// Containers are still collapsed or expanded as usual, the callback is
+    /// run afterwards with the row of the container, e.g. for opening it in
+    /// a separate view. Unlike [`set_on_submit`](#method.set_on_submit) this
+    /// is also invoked in case the container could not be toggled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_submit_container(|siv: &mut Cursive, row: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_submit_container<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+    {
+        self.on_submit_container = Some(Arc::new(move |s, row| cb(s, row)));
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed while a container
+    /// item is selected.
+    ///
+    /// Chainable variant.
+    pub fn on_submit_container<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_submit_container(cb))
+    }
+
+    /// Sets a callback to be used when an item is selected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_select(|siv: &mut Cursive, row: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_select<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+    {
+        self.on_select = Some(Arc::new(move |s, row| cb(s, row)));
+    }
+
+    /// Sets a callback to be used when an item is selected.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.on_select(|siv: &mut Cursive, row: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn on_select<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_select(cb))
+    }
+
+    /// Sets a callback to be used when an item has its children collapsed or expanded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_collapse(|siv: &mut Cursive, row: usize, is_collapsed: bool, children: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_collapse<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize, bool, usize) + Send + Sync + 'static,
+    {
+        self.on_collapse = Some(Arc::new(move |s, row, collapsed, children| {
+            cb(s, row, collapsed, children)
+        }));
+    }
+
+    /// Sets a callback to be used when an item has its children collapsed or expanded.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.on_collapse(|siv: &mut Cursive, row: usize, is_collapsed: bool, children: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn on_collapse<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize, bool, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_collapse(cb))
+    }
+
+    /// Sets a callback to be used when an item has its children expanded.
+    ///
+    /// Unlike the [collapse callback](#method.set_on_collapse) this is only
+    /// invoked in one direction, receiving the row and the number of its
+    /// children. Both callbacks are invoked in case both are set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_expand(|siv: &mut Cursive, row: usize, children: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_expand<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize, usize) + Send + Sync + 'static,
+    {
+        self.on_expand = Some(Arc::new(cb));
+    }
+
+    /// Sets a callback to be used when an item has its children expanded.
+    ///
+    /// Chainable variant.
+    pub fn on_expand<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_expand(cb))
+    }
+
+    /// Sets a callback to be used when an item has its children collapsed.
+    ///
+    /// Counterpart of [`set_on_expand`](#method.set_on_expand).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_collapse_only(|siv: &mut Cursive, row: usize, children: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_collapse_only<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize, usize) + Send + Sync + 'static,
+    {
+        self.on_collapse_only = Some(Arc::new(cb));
+    }
+
+    /// Sets a callback to be used when an item has its children collapsed.
+    ///
+    /// Chainable variant.
+    pub fn on_collapse_only<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_collapse_only(cb))
+    }
+
+    /// Sets a callback to be used when the user checks or unchecks an item
+    /// via `<Space>` while [checkboxes](#method.set_checkboxes) are enabled.
+    ///
+    /// The callback receives the row and whether the item is now checked.
+    /// It is not invoked for the descendants which change along with the
+    /// item, nor for [`set_checked`](#method.set_checked).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_check(|siv: &mut Cursive, row: usize, checked: bool| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_check<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize, bool) + Send + Sync + 'static,
+    {
+        self.on_check = Some(Arc::new(cb));
+    }
+
+    /// Sets a callback to be used when the user checks or unchecks an item.
+    ///
+    /// Chainable variant.
+    pub fn on_check<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize, bool) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_check(cb))
+    }
+
+    /// Sets a callback to be used when `<Up>` is pressed on the first row or
+    /// `<Down>` is pressed on the last row.
+    ///
+    /// Without a callback these keys are ignored at the edges, which allows
+    /// a parent view to move the focus elsewhere. Once set, they are consumed
+    /// and the callback is run with the [`Edge`](enum.Edge.html) that was
+    /// reached instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::{Edge, TreeView};
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_edge(|siv: &mut Cursive, edge: Edge| {
+    ///     if edge == Edge::Bottom {
+    ///         siv.focus_name("details").ok();
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_edge<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, Edge) + Send + Sync + 'static,
+    {
+        self.on_edge = Some(Arc::new(cb));
+    }
+
+    /// Sets a callback to be used when `<Up>` is pressed on the first row or
+    /// `<Down>` is pressed on the last row.
+    ///
+    /// Chainable variant.
+    pub fn on_edge<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, Edge) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_edge(cb))
+    }
+
+    /// Sets a callback to be used before a collapsed item is expanded by
+    /// the user, including the focused item when its subtree is expanded via
+    /// `*`.
+    ///
+    /// The callback receives the tree itself along with the row of the item,
+    /// allowing it to lazily insert the children of the item before they are
+    /// drawn for the first time. Returning `false` cancels the expansion, in
+    /// which case neither the [pre collapse](#method.set_on_pre_collapse) nor
+    /// the collapse callbacks are invoked.
+    ///
+    /// The callback runs before the pre collapse callback, which therefore
+    /// sees the number of children after they have been inserted. Items must
+    /// not be inserted or removed above the given row.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_before_expand(|tree: &mut TreeView<String>, row: usize| {
+    ///     if tree.children_count(row) == Some(0) {
+    ///         tree.insert_item("Loaded".to_string(), Placement::LastChild, row);
+    ///     }
+    ///     true
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_before_expand<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut TreeView<T>, usize) -> bool + Send + Sync + 'static,
+    {
+        self.on_before_expand = Some(Arc::new(cb));
+    }
+
+    /// Sets a callback to be used before a collapsed item is expanded by
+    /// the user, including the focused item when its subtree is expanded via
+    /// `*`.
+    ///
+    /// Chainable variant.
+    pub fn on_before_expand<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut TreeView<T>, usize) -> bool + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_before_expand(cb))
+    }
+
+    /// Sets a loader which populates the children of container items on
+    /// demand.
+    ///
+    /// The first time the user expands a container without any children, the
+    /// loader is asked for them and they are inserted before the container
+    /// is drawn expanded. This happens after the
+    /// [before expand](#method.set_on_before_expand) and
+    /// [pre collapse](#method.set_on_pre_collapse) callbacks allowed the
+    /// expansion, so a cancelled expansion does not load anything. Each
+    /// container is only loaded once, unless its children are removed
+    /// via [`remove_children`](#method.remove_children), which makes the
+    /// next expansion load them again. Programmatic changes via methods like
+    /// `expand_item` do not invoke the loader.
+    ///
+    /// Besides implementations of [`LazyTree`](trait.LazyTree.html), plain
+    /// closures can be used as loaders:
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// let tree = TreeView::<String>::new().lazy_loader(|item: &String| {
+    ///     vec![(format!("{}.1", item), false)]
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{LazyTree, Placement, TreeView};
+    /// # fn main() {
+    /// struct Numbers;
+    ///
+    /// impl LazyTree<String> for Numbers {
+    ///     fn load_children(&mut self, item: &String) -> Vec<(String, bool)> {
+    ///         (1..=3).map(|i| (format!("{}.{}", item, i), true)).collect()
+    ///     }
+    /// }
+    ///
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_container_item("1".to_string(), Placement::After, 0);
+    /// tree.set_lazy_loader(Numbers);
+    /// # }
+    /// ```
+    pub fn set_lazy_loader<L>(&mut self, loader: L)
+    where
+        L: LazyTree<T> + Send + 'static,
+    {
+        self.lazy_loader = Some(Arc::new(Mutex::new(loader)));
+    }
+
+    /// Sets a loader which populates the children of container items on
+    /// demand.
+    ///
+    /// Chainable variant.
+    pub fn lazy_loader<L>(self, loader: L) -> Self
+    where
+        L: LazyTree<T> + Send + 'static,
+    {
+        self.with(|t| t.set_lazy_loader(loader))
+    }
+
+    /// Sets a loader which populates the children of container items in the
+    /// background.
+    ///
+    /// The first time the user expands a container without any children, a
+    /// disabled placeholder row is inserted as its only child and the loader
+    /// is asked to start loading the actual children. These are expected to
+    /// be delivered via [`replace_children`](#method.replace_children) once
+    /// available, usually from another thread via `Cursive::cb_sink`.
+    ///
+    /// Like with [lazy loaders](#method.set_lazy_loader), each container is
+    /// only loaded once unless its children are removed. In case both kinds
+    /// of loaders are set, only the synchronous one is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{AsyncLazyTree, ItemHandle, Placement, TreeView};
+    /// # use std::sync::mpsc::Sender;
+    /// # fn main() {
+    /// struct Remote(Sender<(String, ItemHandle)>);
+    ///
+    /// impl AsyncLazyTree<String> for Remote {
+    ///     fn placeholder(&mut self, _: &String) -> String {
+    ///         "Loading…".to_string()
+    ///     }
+    ///
+    ///     fn request_children(&mut self, item: &String, handle: ItemHandle) {
+    ///         self.0.send((item.clone(), handle)).ok();
+    ///     }
+    /// }
+    ///
+    /// let (tx, _rx) = std::sync::mpsc::channel();
+    /// let mut tree = TreeView::<String>::new().async_loader(Remote(tx));
+    /// tree.insert_container_item("1".to_string(), Placement::After, 0);
+    /// # }
+    /// ```
+    pub fn set_async_loader<L>(&mut self, loader: L)
+    where
+        L: AsyncLazyTree<T> + Send + 'static,
+    {
+        self.async_loader = Some(Arc::new(Mutex::new(loader)));
+    }
+
+    /// Sets a loader which populates the children of container items in the
+    /// background.
+    ///
+    /// Chainable variant.
+    pub fn async_loader<L>(self, loader: L) -> Self
+    where
+        L: AsyncLazyTree<T> + Send + 'static,
+    {
+        self.with(|t| t.set_async_loader(loader))
+    }
+
+    /// Sets a closure which supplies the children of container items on
+    /// demand.
+    ///
+    /// This is a shorthand for [`set_lazy_loader`](#method.set_lazy_loader)
+    /// with a closure, which is called with the container about to be
+    /// expanded and returns its children as pairs of values and whether they
+    /// are containers themselves.
+    pub fn set_children_provider<F>(&mut self, provider: F)
+    where
+        F: FnMut(&T) -> Vec<(T, bool)> + Send + 'static,
+    {
+        self.set_lazy_loader(provider);
+    }
+
+    /// Sets a closure which supplies the children of container items on
+    /// demand.
+    ///
+    /// Chainable variant.
+    pub fn children_provider<F>(self, provider: F) -> Self
+    where
+        F: FnMut(&T) -> Vec<(T, bool)> + Send + 'static,
+    {
+        self.with(|t| t.set_children_provider(provider))
+    }
+
+    /// Sets a callback to be used before an item has its children collapsed
+    /// or expanded by the user.
+    ///
+    /// The callback receives the row, the collapsed state the item is about
+    /// to change to and the number of its children. Returning `false` cancels
+    /// the change, in which case the collapse callback is not invoked either.
+    ///
+    /// Unlike the [collapse callback](#method.set_on_collapse), which is
+    /// returned from the event and only runs once the change was applied,
+    /// this callback has to decide while the view is still processing the
+    /// event and therefore receives no `Cursive`. A
+    /// [`CbSink`](../cursive/struct.CbSink.html) can be captured instead in
+    /// order to react to a cancelled change, e.g. by showing a dialog.
+    ///
+    /// Programmatic changes via methods like `set_collapsed` do not invoke
+    /// this callback.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::views::Dialog;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut siv = cursive::Cursive::new();
+    /// # let mut tree = TreeView::<String>::new();
+    /// let sink = siv.cb_sink().clone();
+    /// tree.set_on_pre_collapse(move |row: usize, is_collapsed: bool, children: usize| {
+    ///     if !is_collapsed && children == 0 {
+    ///         sink.send(Box::new(|s| s.add_layer(Dialog::info("Nothing to show"))))
+    ///             .ok();
+    ///         return false;
+    ///     }
+    ///     true
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_pre_collapse<F>(&mut self, cb: F)
+    where
+        F: Fn(usize, bool, usize) -> bool + Send + Sync + 'static,
+    {
+        self.on_pre_collapse = Some(Arc::new(move |row, collapsed, children| {
+            cb(row, collapsed, children)
+        }));
+    }
+
+    /// Sets a callback to be used before an item has its children collapsed
+    /// or expanded by the user.
+    ///
+    /// Chainable variant.
+    pub fn on_pre_collapse<F>(self, cb: F) -> Self
+    where
+        F: Fn(usize, bool, usize) -> bool + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_pre_collapse(cb))
+    }
+
+    /// Sets a callback to be used before the focus is moved away from a row
+    /// by the user.
+    ///
+    /// The callback receives the row which is about to lose the focus.
+    /// Returning `false` keeps the focus on that row, in which case the
+    /// event is consumed and the select callback is not invoked. Otherwise
+    /// the select callback is invoked for the newly focused row as usual.
+    ///
+    /// Like the [pre collapse callback](#method.set_on_pre_collapse) this
+    /// has to decide while the view is still processing the event and
+    /// therefore receives no `Cursive`, which is only available to the
+    /// callbacks returned from the event. A
+    /// [`CbSink`](../cursive/struct.CbSink.html) can be captured instead in
+    /// order to report a failed validation. Programmatic changes via methods
+    /// like `set_selected_row` do not invoke this callback.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_leave(|row: usize| {
+    ///     // Only allow leaving rows which passed validation
+    ///     row != 0
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_leave<F>(&mut self, cb: F)
+    where
+        F: Fn(usize) -> bool + Send + Sync + 'static,
+    {
+        self.on_leave = Some(Arc::new(cb));
+    }
+
+    /// Sets a callback to be used before the focus is moved away from a row
+    /// by the user.
+    ///
+    /// Chainable variant.
+    pub fn on_leave<F>(self, cb: F) -> Self
+    where
+        F: Fn(usize) -> bool + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_leave(cb))
+    }
+
+    /// Removes all items from this view.
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.focus = 0;
+    }
+
+    /// Removes all items from this view, returning them.
+    pub fn take_items(&mut self) -> Vec<T> {
+        let items = self.list.take_items();
+        self.focus = 0;
+        items
+    }
+
+    /// Returns the number of items in this tree.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if this tree has no items.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the number of currently visible rows, i.e. all rows which are
+    /// not hidden within collapsed parents.
+    pub fn visible_height(&self) -> usize {
+        self.list.height()
+    }
+
+    /// Returns the first visible row.
+    ///
+    /// `None` is returned in case no rows are visible.
+    pub fn first_row(&self) -> Option<usize> {
+        self.last_row().map(|_| 0)
+    }
+
+    /// Returns the last visible row.
+    ///
+    /// `None` is returned in case no rows are visible.
+    pub fn last_row(&self) -> Option<usize> {
+        self.list.height().checked_sub(1)
+    }
+
+    /// Returns the range of rows which were visible within the viewport
+    /// during the last draw, as a pair of the first visible row and the row
+    /// after the last visible one.
+    ///
+    /// When the tree is wrapped in a
+    /// [`ScrollView`](../cursive/views/struct.ScrollView.html) this only
+    /// covers the rows which have been scrolled into view.
+    ///
+    /// `(0, 0)` is returned in case the tree has not been drawn yet.
+    pub fn viewport(&self) -> (usize, usize) {
+        let (start, end) = *self.viewport.lock().unwrap();
+        let height = self.list.height();
+        if start == end {
+            (0, 0)
+        } else {
+            (
+                cmp::min(self.row_at(start), height),
+                cmp::min(self.row_at(end - 1) + 1, height),
+            )
+        }
+    }
+
+    /// Returns the index of the currently selected tree row.
+    ///
+    /// `None` is returned in case of the tree being empty or the selection
+    /// having been cleared via [`deselect`](#method.deselect).
+    pub fn row(&self) -> Option<usize> {
+        if self.is_empty() || self.deselected {
+            None
+        } else {
+            Some(self.focus)
+        }
+    }
+
+    /// Returns position on the x axis of the symbol (first character of an item) at the given row.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn first_col(&self, row: usize) -> Option<usize> {
+        let index = self.list.row_to_item_index(row);
+        self.list.first_col(index)
+    }
+
+    /// Returns the level of the item at the given row within the tree.
+    ///
+    /// Top level items are at level `0`.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{TreeView, Placement};
+    /// # fn main() {
+    /// let mut tree = TreeView::new();
+    /// tree.insert_item("root".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("child".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("grandchild".to_string(), Placement::LastChild, 1);
+    /// tree.insert_item("sibling".to_string(), Placement::After, 0);
+    ///
+    /// assert_eq!(tree.item_level(2), Some(2));
+    ///
+    /// tree.collapse_item(1);
+    /// assert_eq!(tree.item_level(2), Some(0));
+    /// assert_eq!(tree.item_level(3), None);
+    /// # }
+    /// ```
+    pub fn item_level(&self, row: usize) -> Option<usize> {
+        let index = self.list.row_to_item_index(row);
+        self.list.level(index)
+    }
+
+    /// Returns whether the children of the item at the given row are
+    /// currently collapsed.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn is_collapsed(&self, row: usize) -> Option<bool> {
+        let index = self.list.row_to_item_index(row);
+        self.list.is_collapsed(index)
+    }
+
+    /// Returns `true` if the item at the given row is a container.
+    ///
+    /// Containers can be collapsed and expanded even if they do not contain
+    /// any children.
+    ///
+    /// `false` is returned in case the specified `row` does not visually exist.
+    pub fn is_container(&self, row: usize) -> bool {
+        let index = self.list.row_to_item_index(row);
+        self.list.is_container_item(index)
+    }
+
+    /// Returns whether the item at the given `row` is a leaf, i.e. neither a
+    /// container nor has any children.
+    ///
+    /// Use [`descendant_count`](#method.descendant_count) for the number of
+    /// items within the subtree of other items.
+    ///
+    /// `false` is returned in case the specified `row` does not visually exist.
+    pub fn is_leaf(&self, row: usize) -> bool {
+        let index = self.list.row_to_item_index(row);
+        self.list.get(index).is_some()
+            && !self.list.is_container_item(index)
+            && self.list.get_children(index) == 0
+    }
+
+    /// Returns total width (including the symbol) of the item at the given row.
+    ///
+    /// The width is measured in terminal columns, so double width characters
+    /// count twice and combining marks not at all.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn item_width(&self, row: usize) -> Option<usize> {
+        let index = self.list.row_to_item_index(row);
+        self.list
+            .items()
+            .get(index)
+            .map(|item| self.text_width(item) + self.prefix_width())
+    }
+
+    /// Returns the currently visible rows which are part of the selection
+    /// made in multi select mode, in top to bottom order.
+    pub fn selected_rows(&self) -> Vec<usize> {
+        let items = self.list.items();
+        self.list
+            .visible_indices()
+            .enumerate()
+            .filter(|&(_, index)| items[index].is_selected())
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Checks or unchecks the item at the given `row` along with all of its
+    /// descendants, including hidden ones.
+    pub fn set_checked(&mut self, row: usize, checked: bool) {
+        let index = self.list.row_to_item_index(row);
+        self.list.set_checked(index, checked);
+    }
+
+    /// Returns the checkbox state of the item at the given `row`.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn check_state(&self, row: usize) -> Option<CheckState> {
+        let index = self.list.row_to_item_index(row);
+        self.list.check_state(index)
+    }
+
+    /// Returns the currently visible rows whose items are fully checked, in
+    /// top to bottom order.
+    pub fn checked_rows(&self) -> Vec<usize> {
+        self.list
+            .visible_indices()
+            .enumerate()
+            .filter(|&(_, index)| self.list.check_state(index) == Some(CheckState::Checked))
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Returns the item indices of all fully checked items, including those
+    /// hidden within collapsed parents, in top to bottom order.
+    pub fn checked_indices(&self) -> Vec<usize> {
+        (0..self.list.len())
+            .filter(|&index| self.list.check_state(index) == Some(CheckState::Checked))
+            .collect()
+    }
+
+    /// Selects the row at the specified index.
+    ///
+    /// Rows past the end of the tree are limited to the last visible row.
+    ///
+    /// Unless enabled via
+    /// [`set_notify_on_programmatic_select`](#method.set_notify_on_programmatic_select),
+    /// this does not count as a selection change for
+    /// [`take_selection_changed`](#method.take_selection_changed).
+    pub fn set_selected_row(&mut self, row: usize) {
+        self.focus = cmp::min(row, self.list.height().saturating_sub(1));
+        self.deselected = false;
+        if !self.notify_programmatic {
+            self.notified_id = self.selected_id();
+        }
+    }
+
+    /// Selects the row at the specified index, returning the callbacks set
+    /// via [`set_on_select`](#method.set_on_select) for it.
+    ///
+    /// Since there is no `Cursive` instance available here, the returned
+    /// callback is not run automatically; the caller must execute it, e.g.
+    /// in order to populate a detail pane for the initial selection.
+    ///
+    /// `None` is returned in case no select callback is set or the specified
+    /// `row` does not visually exist, in which case the last visible row is
+    /// selected like [`set_selected_row`](#method.set_selected_row) does.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// # let mut siv = Cursive::new();
+    /// let mut tree = TreeView::<String>::new().on_select(|siv: &mut Cursive, row: usize| {
+    ///
+    /// });
+    /// tree.insert_item("1".to_string(), Placement::After, 0);
+    ///
+    /// if let Some(cb) = tree.set_selected_row_notify(0) {
+    ///     cb(&mut siv);
+    /// }
+    /// # }
+    /// ```
+    pub fn set_selected_row_notify(&mut self, row: usize) -> Option<Callback> {
+        self.set_selected_row(row);
+        self.notified_id = self.selected_id();
+        if row < self.list.height() {
+            self.select_callback(row)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the selected item changed since the last call, the
+    /// last `on_select` callback emitted by an event, or the last call to
+    /// [`set_selected_row`](#method.set_selected_row).
+    ///
+    /// Changes to the selection caused by mutating the tree, e.g. by removing
+    /// or collapsing the focused item, do not trigger the `on_select`
+    /// callback since there is no `Cursive` instance to run it with.
+    /// Applications can poll this method after such mutations instead.
+    pub fn take_selection_changed(&mut self) -> bool {
+        let id = self.selected_id();
+        let changed = id != self.notified_id;
+        self.notified_id = id;
+        changed
+    }
+
+    /// Enables or disables reporting selection changes made via
+    /// [`set_selected_row`](#method.set_selected_row) through
+    /// [`take_selection_changed`](#method.take_selection_changed).
+    ///
+    /// Disabled by default.
+    pub fn set_notify_on_programmatic_select(&mut self, notify: bool) {
+        self.notify_programmatic = notify;
+    }
+
+    /// Enables or disables reporting selection changes made via
+    /// [`set_selected_row`](#method.set_selected_row) through
+    /// [`take_selection_changed`](#method.take_selection_changed).
+    ///
+    /// Chainable variant.
+    pub fn notify_on_programmatic_select(self, notify: bool) -> Self {
+        self.with(|t| t.set_notify_on_programmatic_select(notify))
+    }
+
+    /// Clears the selection, so that no row is highlighted and
+    /// [`row`](#method.row) returns `None`.
+    ///
+    /// Any navigation key selects the first row again, while `<Enter>` is
+    /// ignored until a row has been selected.
+    pub fn deselect(&mut self) {
+        self.deselected = true;
+    }
+
+    /// Enables or disables clearing the selection by pressing `<Esc>`.
+    ///
+    /// See [`deselect`](#method.deselect) for details.
+    pub fn set_allow_deselect(&mut self, allow: bool) {
+        self.allow_deselect = allow;
+    }
+
+    /// Enables or disables clearing the selection by pressing `<Esc>`.
+    ///
+    /// Chainable variant.
+    pub fn allow_deselect(self, allow: bool) -> Self {
+        self.with(|t| t.set_allow_deselect(allow))
+    }
+
+    /// Selects the row at the specified index, limited to the last visible
+    /// row like [`set_selected_row`](#method.set_selected_row) does.
+    ///
+    /// Chainable variant.
+    pub fn selected_row(self, row: usize) -> Self {
+        self.with(|t| t.set_selected_row(row))
+    }
+
+    /// Returns the vertical scroll offset at which the given `row` is the
+    /// first one within the viewport, i.e. the line it starts on.
+    ///
+    /// Rows past the end of the tree are limited to the last one.
+    ///
+    /// The tree never scrolls on its own, a wrapping
+    /// [`ScrollView`](../cursive/views/struct.ScrollView.html) only follows
+    /// the selection once it is moved. So passing the returned offset to its
+    /// `set_offset` method scrolls to a different part of the tree while
+    /// keeping the selection where it is, same as the mouse wheel does.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::view::Scrollable;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut scroll = TreeView::<usize>::new().scrollable();
+    /// for i in 0..100 {
+    ///     scroll.get_inner_mut().insert_item(i, Placement::After, i);
+    /// }
+    ///
+    /// let offset = scroll.get_inner().row_offset(80);
+    /// scroll.set_offset((0, offset));
+    /// assert_eq!(scroll.get_inner().row(), Some(0));
+    /// # }
+    /// ```
+    pub fn row_offset(&self, row: usize) -> usize {
+        self.row_y(cmp::min(row, self.list.height().saturating_sub(1)))
+    }
+
+    /// Returns the vertical scroll offset at which the given `row` is
+    /// centered within a viewport of `viewport_height` lines.
+    ///
+    /// The offset is clamped so that the viewport does not scroll past the
+    /// end of the tree.
+    ///
+    /// Scrolling itself is handled by a wrapping
+    /// [`ScrollView`](../cursive/views/struct.ScrollView.html), which only
+    /// ensures the selected row is visible; the returned offset can be passed
+    /// to its `set_offset` method instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::view::Scrollable;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut scroll = TreeView::<usize>::new().scrollable();
+    /// for i in 0..100 {
+    ///     scroll.get_inner_mut().insert_item(i, Placement::After, i);
+    /// }
+    ///
+    /// let row = 50;
+    /// let height = scroll.content_viewport().height();
+    /// let offset = scroll.get_inner().centered_offset(row, height);
+    ///
+    /// scroll.get_inner_mut().set_selected_row(row);
+    /// scroll.set_offset((0, offset));
+    /// # }
+    /// ```
+    pub fn centered_offset(&self, row: usize, viewport_height: usize) -> usize {
+        let total = self.row_y(self.list.height());
+        let center = self.row_y(row) + self.row_lines(row) / 2;
+        cmp::min(
+            center.saturating_sub(viewport_height / 2),
+            total.saturating_sub(viewport_height),
+        )
+    }
+
+    /// Returns a immutable reference to the item at the given row.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn borrow_item(&self, row: usize) -> Option<&T> {
+        let index = self.list.row_to_item_index(row);
+        self.list.get(index)
+    }
+
+    /// Returns a mutable reference to the item at the given row.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn borrow_item_mut(&mut self, row: usize) -> Option<&mut T> {
+        let index = self.list.row_to_item_index(row);
+        self.list.get_mut(index)
+    }
+
+    /// Applies `f` to the item at the given row and updates the layout of the
+    /// view to match its new value.
+    ///
+    /// Unlike edits through [`borrow_item_mut`](#method.borrow_item_mut),
+    /// changes which affect the width of the item, the row offsets of wrapped
+    /// items or whether it matches the [filter](#method.set_filter) take
+    /// effect right away instead of on the next layout pass.
+    ///
+    /// Returns `false` in case the specified `row` does not visually exist,
+    /// in which case `f` is not called.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("file".to_string(), Placement::After, 0);
+    ///
+    /// assert!(tree.update_item(0, |name| name.push_str(".rs")));
+    /// assert_eq!(tree.borrow_item(0), Some(&"file.rs".to_string()));
+    /// assert!(!tree.update_item(1, |name| name.clear()));
+    /// # }
+    /// ```
+    pub fn update_item<F: FnOnce(&mut T)>(&mut self, row: usize, f: F) -> bool {
+        match self.borrow_item_mut(row) {
+            Some(item) => f(item),
+            None => return false,
+        }
+
+        let size = self.last_size;
+        self.update_layout(size);
+        true
+    }
+
+    /// Replaces the item at the given row with `value`, returning the
+    /// previous item.
+    ///
+    /// Like with [`update_item`](#method.update_item) the layout of the
+    /// view is updated to match the new value right away.
+    ///
+    /// `None` is returned in case the specified `row` does not visually
+    /// exist, in which case `value` is dropped.
+    pub fn set_item(&mut self, row: usize, value: T) -> Option<T> {
+        let mut old = None;
+        self.update_item(row, |item| old = Some(mem::replace(item, value)));
+        old
+    }
+
+    /// Returns a immutable reference to the item at the given structural
+    /// `path`.
+    ///
+    /// Each entry of the path is the position of an item among its siblings,
+    /// starting with the top level items, i.e. `[0, 2, 1]` refers to the
+    /// second child of the third child of the first top level item. Paths do
+    /// not depend on the collapsed state of any item.
+    ///
+    /// `None` is returned in case no item exists at `path`.
+    pub fn borrow_item_at_path(&self, path: &[usize]) -> Option<&T> {
+        let index = self.list.path_to_item_index(path)?;
+        self.list.get(index)
+    }
+
+    /// Returns a mutable reference to the item at the given structural
+    /// `path`.
+    ///
+    /// See [`borrow_item_at_path`](#method.borrow_item_at_path) for details
+    /// on paths.
+    ///
+    /// `None` is returned in case no item exists at `path`.
+    pub fn borrow_item_at_path_mut(&mut self, path: &[usize]) -> Option<&mut T> {
+        let index = self.list.path_to_item_index(path)?;
+        self.list.get_mut(index)
+    }
+
+    /// Returns an [`ItemHandle`](struct.ItemHandle.html) for the item at the
+    /// given row, which keeps referring to the same item regardless of later
+    /// changes to the tree.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("b".to_string(), Placement::After, 0);
+    /// let handle = tree.handle(0).unwrap();
+    ///
+    /// tree.insert_item("a".to_string(), Placement::Before, 0);
+    /// assert_eq!(tree.row_for_handle(&handle), Some(1));
+    ///
+    /// tree.remove_by_handle(&handle);
+    /// assert_eq!(tree.row_for_handle(&handle), None);
+    /// # }
+    /// ```
+    pub fn handle(&self, row: usize) -> Option<ItemHandle> {
+        let index = self.list.row_to_item_index(row);
+        self.list
+            .items()
+            .get(index)
+            .map(|item| ItemHandle(item.id()))
+    }
+
+    /// Returns the visual row of the item referred to by `handle`.
+    ///
+    /// `None` is returned in case the item has been removed or is hidden
+    /// within a collapsed parent.
+    pub fn row_for_handle(&self, handle: &ItemHandle) -> Option<usize> {
+        let index = self.list.id_to_item_index(handle.0)?;
+        if self.list.is_visible(index) {
+            Some(self.list.item_index_to_row(index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a immutable reference to the item referred to by `handle`.
+    ///
+    /// `None` is returned in case the item has been removed.
+    pub fn borrow_item_by_handle(&self, handle: &ItemHandle) -> Option<&T> {
+        let index = self.list.id_to_item_index(handle.0)?;
+        self.list.get(index)
+    }
+
+    /// Returns a mutable reference to the item referred to by `handle`.
+    ///
+    /// `None` is returned in case the item has been removed.
+    pub fn borrow_item_by_handle_mut(&mut self, handle: &ItemHandle) -> Option<&mut T> {
+        let index = self.list.id_to_item_index(handle.0)?;
+        self.list.get_mut(index)
+    }
+
+    /// Returns an iterator over all currently visible rows and their items
+    /// in top to bottom order.
+    ///
+    /// Items hidden within collapsed parents are skipped, so the yielded
+    /// rows can be passed straight back into any of the row based methods.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{TreeView, Placement};
+    /// # fn main() {
+    /// let mut tree = TreeView::new();
+    /// tree.insert_item("root".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("child".to_string(), Placement::LastChild, 0);
+    /// tree.collapse_item(0);
+    ///
+    /// let rows: Vec<_> = tree.iter().collect();
+    /// assert_eq!(rows, vec![(0, &"root".to_string())]);
+    /// # }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        let items = self.list.items();
+        self.list
+            .visible_indices()
+            .enumerate()
+            .map(move |(row, index)| (row, items[index].value()))
+    }
+
+    /// Returns an iterator over all items in this tree, including those
+    /// hidden within collapsed parents.
+    ///
+    /// Each entry consists of the item index, the level of the item within
+    /// the tree and the item itself.
+    pub fn iter_all(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.list
+            .items()
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (index, item.level(), item.value()))
+    }
+
+    /// Returns the level, the container flag and the value of all items in
+    /// top to bottom order, including those hidden within collapsed parents.
+    ///
+    /// The tree can be rebuilt from the returned structure via
+    /// [`from_level_vec`](#method.from_level_vec).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_container_item("src".to_string(), Placement::After, 0);
+    /// tree.insert_item("lib.rs".to_string(), Placement::LastChild, 0);
+    ///
+    /// let levels: Vec<_> = tree
+    ///     .to_level_vec()
+    ///     .into_iter()
+    ///     .map(|(level, is_container, item)| (level, is_container, item.clone()))
+    ///     .collect();
+    ///
+    /// let copy = TreeView::from_level_vec(levels);
+    /// assert_eq!(copy.to_level_vec(), tree.to_level_vec());
+    /// # }
+    /// ```
+    pub fn to_level_vec(&self) -> Vec<(usize, bool, &T)> {
+        self.list
+            .items()
+            .iter()
+            .map(|item| (item.level(), item.is_container(), item.value()))
+            .collect()
+    }
+
+    /// Creates a new `TreeView` from `(level, is_container, item)` triples in
+    /// top to bottom order, as returned by
+    /// [`to_level_vec`](#method.to_level_vec).
+    ///
+    /// Containers start out collapsed, just like they do when inserted via
+    /// [`insert_container_item`](#method.insert_container_item), while other
+    /// items with children become expanded containers. Levels deeper than
+    /// one below the previous item are limited to that depth.
+    pub fn from_level_vec<I: IntoIterator<Item = (usize, bool, T)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.list = TreeList::from_levels(iter);
+        tree
+    }
+
+    /// Creates a new `TreeView` from the nested items described by `root`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{TreeBuilder, TreeView};
+    /// # fn main() {
+    /// let tree = TreeView::from_builder(TreeBuilder::Node(
+    ///     "src".to_string(),
+    ///     vec![
+    ///         TreeBuilder::Node(
+    ///             "tree_list".to_string(),
+    ///             vec![TreeBuilder::Leaf("mod.rs".to_string())],
+    ///         ),
+    ///         TreeBuilder::Leaf("lib.rs".to_string()),
+    ///     ],
+    /// ));
+    /// assert_eq!(tree.borrow_item(2), Some(&"mod.rs".to_string()));
+    /// # }
+    /// ```
+    pub fn from_builder(root: TreeBuilder<T>) -> Self {
+        let mut tree = Self::new();
+        tree.append_builder(0, root);
+        tree
+    }
+
+    /// Inserts the nested items described by `builder` as the last child of
+    /// the given `parent_row`, returning the visual row of the outermost
+    /// inserted item.
+    ///
+    /// In case the tree is empty, the items are inserted at the top level.
+    ///
+    /// `None` will be returned in case the outermost item is not visible
+    /// after insertion due to `parent_row` or one of its parents being in a
+    /// collapsed state.
+    pub fn append_builder(&mut self, parent_row: usize, builder: TreeBuilder<T>) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(parent_row);
+        let inserted = self.insert_builder(Placement::LastChild, index, builder);
+        self.restore_focus(id);
+        inserted
+    }
+
+    /// Returns the visual rows of all visible items for which the predicate
+    /// `p` returns `true`.
+    ///
+    /// Items hidden within collapsed parents are not searched, see
+    /// [`find_indices`](#method.find_indices) for a variant which includes
+    /// them.
+    pub fn find_rows<P: Fn(&T) -> bool>(&self, p: P) -> Vec<usize> {
+        self.iter()
+            .filter(|(_, item)| p(item))
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Returns the visual row of the first visible item for which the
+    /// predicate `p` returns `true`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("root".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("child".to_string(), Placement::LastChild, 0);
+    ///
+    /// if let Some(row) = tree.find_first(|item| item == "child") {
+    ///     tree.set_selected_row(row);
+    /// }
+    /// assert_eq!(tree.row(), Some(1));
+    /// # }
+    /// ```
+    pub fn find_first<P: Fn(&T) -> bool>(&self, p: P) -> Option<usize> {
+        self.iter().find(|(_, item)| p(item)).map(|(row, _)| row)
+    }
+
+    /// Returns the visual row of the first item for which the predicate
+    /// `pred` returns `true`, searching all items in top to bottom order.
+    ///
+    /// In contrast to [`find_first`](#method.find_first), items hidden within
+    /// collapsed parents are searched as well. For such an item the row of
+    /// its outermost collapsed parent is returned, as that is the row which
+    /// visually contains it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("src".to_string(), Placement::After, 0);
+    /// tree.insert_item("lib.rs".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("Cargo.toml".to_string(), Placement::After, 0);
+    ///
+    /// assert_eq!(tree.find_item(|item| item == "lib.rs"), Some(1));
+    ///
+    /// tree.collapse_item(0);
+    /// assert_eq!(tree.find_item(|item| item == "lib.rs"), Some(0));
+    /// assert_eq!(tree.find_item(|item| item == "Cargo.toml"), Some(1));
+    /// # }
+    /// ```
+    pub fn find_item<P: Fn(&T) -> bool>(&self, pred: P) -> Option<usize> {
+        let index = self
+            .list
+            .items()
+            .iter()
+            .position(|item| pred(item.value()))?;
+        let index = self.list.visible_ancestor(index);
+        Some(self.list.item_index_to_row(index))
+    }
+
+    /// Returns the item indices of all items for which the predicate `p`
+    /// returns `true`, including those hidden within collapsed parents.
+    ///
+    /// The returned indices can be passed to
+    /// [`expand_to`](#method.expand_to) in order to reveal the items.
+    pub fn find_indices<P: Fn(&T) -> bool>(&self, p: P) -> Vec<usize> {
+        self.iter_all()
+            .filter(|(_, _, item)| p(item))
+            .map(|(index, _, _)| index)
+            .collect()
+    }
+
+    /// Inserts a new `item` at the given `row` with the specified
+    /// [`Placement`](enum.Placement.html), returning the visual row of the item
+    /// occupies after its insertion.
+    ///
+    /// The selection stays on the selected item, even if its row changes.
+    ///
+    /// `None` will be returned in case the item is not visible after insertion
+    /// due to one of its parents being in a collapsed state.
+    pub fn insert_item(&mut self, item: T, placement: Placement, row: usize) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let inserted = self.list.insert_item(placement, index, item);
+        self.restore_focus(id);
+        inserted
+    }
+
+    /// Inserts all `items` as a contiguous block of siblings at the given
+    /// `row` with the specified [`Placement`](enum.Placement.html), returning
+    /// the visual row of the first inserted item.
+    ///
+    /// This is considerably faster than inserting many items one by one, as
+    /// the heights of the parents are only updated once.
+    ///
+    /// `None` will be returned in case no items were given, the items are not
+    /// visible after insertion due to one of their parents being in a
+    /// collapsed state, or `Placement::Parent` was used with more than one
+    /// item, in which case nothing is inserted.
+    pub fn insert_items<I: IntoIterator<Item = T>>(
+        &mut self,
+        items: I,
+        placement: Placement,
+        row: usize,
+    ) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let inserted = self
+            .list
+            .insert_items(placement, index, items.into_iter().collect());
+        self.restore_focus(id);
+        inserted
+    }
+
+    /// Inserts a new `container` at the given `row` with the specified
+    /// [`Placement`](enum.Placement.html), returning the visual row of the
+    /// container occupies after its insertion.
+    ///
+    /// A container is identical to a normal item except for the fact that it
+    /// can always be collapsed even if it does not contain any children.
+    ///
+    /// > Note: If the container is not visible because one of its parents is
+    /// > collapsed `None` will be returned since there is no visible row for
+    /// > the container to occupy.
+    pub fn insert_container_item(
+        &mut self,
+        item: T,
+        placement: Placement,
+        row: usize,
+    ) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let inserted = self.list.insert_container_item(placement, index, item);
+        self.restore_focus(id);
+        inserted
+    }
+
+    /// Appends a new top level `item` after the last existing top level item
+    /// and all of its children, returning the visual row it occupies.
+    ///
+    /// On an empty tree, the item becomes the first one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// assert_eq!(tree.push_root("a".to_string()), 0);
+    /// tree.insert_item("a.1".to_string(), Placement::LastChild, 0);
+    /// assert_eq!(tree.push_root("b".to_string()), 2);
+    /// assert_eq!(tree.item_level(2), Some(0));
+    /// # }
+    /// ```
+    pub fn push_root(&mut self, item: T) -> usize {
+        self.push_root_node(item, false)
+    }
+
+    /// Appends a new top level `container` after the last existing top level
+    /// item and all of its children, returning the visual row it occupies.
+    ///
+    /// On an empty tree, the container becomes the first item.
+    pub fn push_root_container(&mut self, item: T) -> usize {
+        self.push_root_node(item, true)
+    }
+
+    /// Inserts a new `item` as a child of the given `row`, placed before the
+    /// first of the existing children which compares greater according to
+    /// `cmp`, returning the visual row the item occupies after its insertion.
+    ///
+    /// Only the immediate children of `row` are compared against, which
+    /// keeps them sorted in case all of them were inserted this way.
+    ///
+    /// `None` will be returned in case the item is not visible after insertion
+    /// due to `row` or one of its parents being in a collapsed state.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("root".to_string(), Placement::After, 0);
+    ///
+    /// for name in &["b", "c", "a"] {
+    ///     tree.insert_item_sorted(name.to_string(), 0, |a, b| a.cmp(b));
+    /// }
+    /// assert_eq!(tree.borrow_item(1), Some(&"a".to_string()));
+    /// # }
+    /// ```
+    pub fn insert_item_sorted<F>(&mut self, item: T, row: usize, cmp: F) -> Option<usize>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let position = self.sorted_position(row, &item, cmp);
+        self.insert_item(item, Placement::NthChild(position), row)
+    }
+
+    /// Inserts a new `container` as a child of the given `row`, placed before
+    /// the first of the existing children which compares greater according
+    /// to `cmp`, returning the visual row the container occupies after its
+    /// insertion.
+    ///
+    /// See [`insert_item_sorted`](#method.insert_item_sorted) for details.
+    pub fn insert_container_item_sorted<F>(&mut self, item: T, row: usize, cmp: F) -> Option<usize>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let position = self.sorted_position(row, &item, cmp);
+        self.insert_container_item(item, Placement::NthChild(position), row)
+    }
+
+    /// Inserts a new `item` so that it ends up at the given structural
+    /// `path`, returning an [`ItemHandle`](struct.ItemHandle.html) for it.
+    ///
+    /// See [`borrow_item_at_path`](#method.borrow_item_at_path) for details
+    /// on paths. In case the last entry of the path exceeds the number of
+    /// siblings, the item is placed after all of them.
+    ///
+    /// `None` is returned in case the parent of the item does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_at_path(&[0], "root".to_string());
+    /// tree.insert_at_path(&[0, 0], "b".to_string());
+    /// tree.insert_at_path(&[0, 0], "a".to_string());
+    ///
+    /// assert_eq!(tree.borrow_item_at_path(&[0, 1]), Some(&"b".to_string()));
+    /// assert!(tree.insert_at_path(&[1, 0], "c".to_string()).is_none());
+    /// # }
+    /// ```
+    pub fn insert_at_path(&mut self, path: &[usize], item: T) -> Option<ItemHandle> {
+        let (&position, parent) = path.split_last()?;
+        let focused = self.focused_id();
+        let id = self.list.next_id();
+        if parent.is_empty() {
+            let roots = self.list.root_indices();
+            match roots.get(position) {
+                Some(&index) => self.list.insert_item(Placement::Before, index, item),
+                None => {
+                    let index = roots.last().cloned().unwrap_or(0);
+                    self.list.insert_item(Placement::After, index, item)
+                }
+            };
+        } else {
+            let index = self.list.path_to_item_index(parent)?;
+            self.list
+                .insert_item(Placement::NthChild(position), index, item);
+        }
+        self.restore_focus(focused);
+        self.list.id_to_item_index(id).map(|_| ItemHandle(id))
+    }
+
+    /// Inserts a new `item` at the given `row` with the specified
+    /// [`Placement`](enum.Placement.html), returning an
+    /// [`ItemHandle`](struct.ItemHandle.html) for it.
+    ///
+    /// In contrast to [`insert_item`](#method.insert_item) a handle is also
+    /// returned in case the item is hidden within a collapsed parent.
+    pub fn insert_item_with_handle(
+        &mut self,
+        item: T,
+        placement: Placement,
+        row: usize,
+    ) -> Option<ItemHandle> {
+        let id = self.list.next_id();
+        self.insert_item(item, placement, row);
+        self.list.id_to_item_index(id).map(|_| ItemHandle(id))
+    }
+
+    /// Inserts a new `container` at the given `row` with the specified
+    /// [`Placement`](enum.Placement.html), returning an
+    /// [`ItemHandle`](struct.ItemHandle.html) for it.
+    ///
+    /// In contrast to [`insert_container_item`](#method.insert_container_item)
+    /// a handle is also returned in case the container is hidden within a
+    /// collapsed parent.
+    pub fn insert_container_item_with_handle(
+        &mut self,
+        item: T,
+        placement: Placement,
+        row: usize,
+    ) -> Option<ItemHandle> {
+        let id = self.list.next_id();
+        self.insert_container_item(item, placement, row);
+        self.list.id_to_item_index(id).map(|_| ItemHandle(id))
+    }
+
+    /// Removes the item at the given `row` along with all of its children.
+    ///
+    /// The selection stays on the selected item in case it is not removed,
+    /// and is otherwise limited to the remaining rows.
+    ///
+    /// The returned vector contains the removed items in top to bottom order.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn remove_item(&mut self, row: usize) -> Option<Vec<T>> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let removed = self.list.remove_with_children(index);
+        self.restore_focus(id);
+        removed
+    }
+
+    /// Removes the item referred to by `handle` along with all of its
+    /// children, even if it is hidden within a collapsed parent.
+    ///
+    /// The returned vector contains the removed items in top to bottom order.
+    ///
+    /// `None` is returned in case the item has already been removed.
+    pub fn remove_by_handle(&mut self, handle: &ItemHandle) -> Option<Vec<T>> {
+        let id = self.focused_id();
+        let index = self.list.id_to_item_index(handle.0)?;
+        let removed = self.list.remove_with_children(index);
+        self.restore_focus(id);
+        removed
+    }
+
+    /// Removes the item at the given structural `path` along with all of its
+    /// children, even if it is hidden within a collapsed parent.
+    ///
+    /// See [`borrow_item_at_path`](#method.borrow_item_at_path) for details
+    /// on paths. The returned vector contains the removed items in top to
+    /// bottom order.
+    ///
+    /// `None` is returned in case no item exists at `path`.
+    pub fn remove_at_path(&mut self, path: &[usize]) -> Option<Vec<T>> {
+        let id = self.focused_id();
+        let index = self.list.path_to_item_index(path)?;
+        let removed = self.list.remove_with_children(index);
+        self.restore_focus(id);
+        removed
+    }
+
+    /// Removes the item at the given `row` along with all of its children,
+    /// keeping the structure of the removed items intact.
+    ///
+    /// The returned [`ExtractedTree`](struct.ExtractedTree.html) can be
+    /// inserted again via [`insert_subtree`](#method.insert_subtree), which
+    /// restores the collapsed state, the container flags as well as the
+    /// [`ItemHandle`](struct.ItemHandle.html)s of all items.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("a".to_string(), Placement::After, 0);
+    /// tree.insert_item("child".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("b".to_string(), Placement::After, 0);
+    ///
+    /// let subtree = tree.remove_subtree(0).unwrap();
+    /// tree.insert_subtree(subtree, Placement::LastChild, 0);
+    ///
+    /// assert_eq!(tree.item_level(2), Some(2));
+    /// # }
+    /// ```
+    pub fn remove_subtree(&mut self, row: usize) -> Option<ExtractedTree<T>> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let nodes = self.list.remove_subtree(index)?;
+        self.restore_focus(id);
+        Some(ExtractedTree { nodes })
+    }
+
+    /// Inserts a `tree` previously removed via
+    /// [`remove_subtree`](#method.remove_subtree) at the given `row` with the
+    /// specified [`Placement`](enum.Placement.html), returning the visual row
+    /// of its root item.
+    ///
+    /// `None` will be returned in case the root item is not visible after
+    /// insertion due to one of its parents being in a collapsed state, or in
+    /// case `Placement::Parent` is used for a tree with more than one item,
+    /// in which case nothing is inserted.
+    ///
+    /// The inserted items receive new [handles](#method.handle), handles
+    /// obtained before the subtree was removed do not refer to them.
+    pub fn insert_subtree(
+        &mut self,
+        tree: ExtractedTree<T>,
+        placement: Placement,
+        row: usize,
+    ) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let inserted = self.list.insert_subtree(placement, index, tree.nodes);
+        self.restore_focus(id);
+        inserted
+    }
+
+    /// Removes all children of the item at the given `row`.
+    ///
+    /// The returned vector contains the removed children in top to bottom order.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn remove_children(&mut self, row: usize) -> Option<Vec<T>> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let removed = self.list.remove_children(index);
+        self.restore_focus(id);
+        removed
+    }
+
+    /// Replaces all children of the item referred to by `handle` with the
+    /// given `items`, as pairs of values and whether they are containers
+    /// themselves.
+    ///
+    /// This is meant for delivering the children requested by an
+    /// [async loader](#method.set_async_loader), but works with any item.
+    /// The collapsed state of the item is kept, so the children are also
+    /// replaced in case it was collapsed again in the meantime.
+    ///
+    /// The returned vector contains the removed children in top to bottom
+    /// order.
+    ///
+    /// `None` is returned in case the item has been removed.
+    pub fn replace_children(
+        &mut self,
+        handle: &ItemHandle,
+        items: Vec<(T, bool)>,
+    ) -> Option<Vec<T>> {
+        let id = self.focused_id();
+        let index = self.list.id_to_item_index(handle.0)?;
+        let removed = self.list.remove_children(index);
+        for (value, is_container) in items {
+            if is_container {
+                self.list
+                    .insert_container_item(Placement::LastChild, index, value);
+            } else {
+                self.list.insert_item(Placement::LastChild, index, value);
+            }
+        }
+        self.list.set_loaded(index, true);
+        self.restore_focus(id);
+        removed
+    }
+
+    /// Removes all items for which the predicate `p` returns `false`,
+    /// including those hidden within collapsed parents.
+    ///
+    /// The children of removed items are handled according to `policy`.
+    /// The focus stays on the selected item in case it is retained and is
+    /// otherwise limited to the remaining rows.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, RetainPolicy, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("src".to_string(), Placement::After, 0);
+    /// tree.insert_item("lib.rs".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("README.md".to_string(), Placement::After, 0);
+    ///
+    /// tree.retain(|item| item.ends_with(".rs"), RetainPolicy::ExtractChildren);
+    /// assert_eq!(tree.len(), 1);
+    /// assert_eq!(tree.borrow_item(0), Some(&"lib.rs".to_string()));
+    /// # }
+    /// ```
+    pub fn retain<P: Fn(&T) -> bool>(&mut self, p: P, policy: RetainPolicy) {
+        let id = self.focused_id();
+        self.list.retain(p, policy);
+        self.restore_focus(id);
+    }
+
+    /// Sorts the immediate children of the item at the given `row` by `cmp`,
+    /// moving each child along with all of its own children.
+    ///
+    /// The sort is stable and keeps the collapsed state of all items. The
+    /// focus stays on the selected item.
+    pub fn sort_children_by<F>(&mut self, row: usize, cmp: F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        self.list.sort_children_by(index, &cmp);
+        self.restore_focus(id);
+    }
+
+    /// Sorts the children of the item at the given `row` and of all of its
+    /// descendants by `cmp`, including those hidden within collapsed parents.
+    ///
+    /// The sort is stable and keeps the collapsed state of all items. The
+    /// focus stays on the selected item.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<usize>::new();
+    /// tree.insert_item(0, Placement::After, 0);
+    /// tree.insert_item(2, Placement::LastChild, 0);
+    /// tree.insert_item(1, Placement::LastChild, 0);
+    ///
+    /// tree.sort_subtree_by(0, |a, b| a.cmp(b));
+    /// assert_eq!(tree.borrow_item(1), Some(&1));
+    /// # }
+    /// ```
+    pub fn sort_subtree_by<F>(&mut self, row: usize, cmp: F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        self.list.sort_subtree_by(index, &cmp);
+        self.restore_focus(id);
+    }
+
+    /// Extracts the item at the given `row` from the tree.
+    ///
+    /// All of the items children will be moved up one level within the tree.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn extract_item(&mut self, row: usize) -> Option<T> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let removed = self.list.remove(index);
+        self.restore_focus(id);
+        removed
+    }
+
+    /// Moves the item at the given `row` along with all of its children to
+    /// the specified [`Placement`](enum.Placement.html) relative to
+    /// `target_row`, returning the visual row the item occupies after the
+    /// move.
+    ///
+    /// The collapsed state of the moved items is preserved and the focus
+    /// stays on the selected item, or moves to the collapsed parent which now
+    /// hides it.
+    ///
+    /// `None` is returned in case either row does not visually exist, the
+    /// target lies within the moved subtree itself, `Placement::Parent` is
+    /// used, or the item is not visible after the move due to one of its new
+    /// parents being in a collapsed state.
+    pub fn move_item(
+        &mut self,
+        row: usize,
+        placement: Placement,
+        target_row: usize,
+    ) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let target = self.list.row_to_item_index(target_row);
+        let moved = self.list.move_item(index, placement, target);
+        self.restore_focus(id);
+        moved
+    }
+
+    /// Moves the item at the given `row` along with all of its children out
+    /// of its parent, placing it as the next sibling of the parent,
+    /// returning the visual row the item occupies afterwards.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist
+    /// or the item is a top level item.
+    pub fn promote_item(&mut self, row: usize) -> Option<usize> {
+        let parent_row = self.parent_row(row)?;
+        self.move_item(row, Placement::After, parent_row)
+    }
+
+    /// Moves the item at the given `row` along with all of its children into
+    /// its previous sibling, placing it as the sibling's last child,
+    /// returning the visual row the item occupies afterwards.
+    ///
+    /// In case the previous sibling is collapsed the item becomes hidden, in
+    /// which case `None` is returned and the focus, if it was on the item,
+    /// moves to the sibling.
+    ///
+    /// `None` is also returned in case the specified `row` does not visually
+    /// exist or the item is the first of its siblings.
+    pub fn demote_item(&mut self, row: usize) -> Option<usize> {
+        let index = self.list.row_to_item_index(row);
+        let sibling = self.list.previous_sibling(index)?;
+        let sibling_row = self.list.item_index_to_row(sibling);
+        self.move_item(row, Placement::LastChild, sibling_row)
+    }
+
+    /// Swaps the item at the given `row` along with all of its children with
+    /// its previous sibling, returning the visual row the item occupies
+    /// afterwards.
+    ///
+    /// The item stays in place in case it already is the first sibling. The
+    /// focus follows the item in case it was the one selected.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn move_item_up(&mut self, row: usize) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let index = self.list.move_up(index)?;
+        self.restore_focus(id);
+        Some(self.list.item_index_to_row(index))
+    }
+
+    /// Swaps the item at the given `row` along with all of its children with
+    /// its next sibling, returning the visual row the item occupies
+    /// afterwards.
+    ///
+    /// The item stays in place in case it already is the last sibling. The
+    /// focus follows the item in case it was the one selected.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn move_item_down(&mut self, row: usize) -> Option<usize> {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        let index = self.list.move_down(index)?;
+        self.restore_focus(id);
+        Some(self.list.item_index_to_row(index))
+    }
+
+    /// Swaps the item at the given `row` along with all of its children with
+    /// its previous sibling, like [`move_item_up`](#method.move_item_up)
+    /// does, returning whether there was such a sibling.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("a".to_string(), Placement::After, 0);
+    /// tree.insert_item("b".to_string(), Placement::After, 0);
+    /// tree.set_selected_row(1);
+    ///
+    /// assert!(tree.swap_with_previous_sibling(1));
+    /// assert_eq!(tree.borrow_item(0), Some(&"b".to_string()));
+    /// assert_eq!(tree.row(), Some(0));
+    /// assert!(!tree.swap_with_previous_sibling(0));
+    /// # }
+    /// ```
+    pub fn swap_with_previous_sibling(&mut self, row: usize) -> bool {
+        let index = self.list.row_to_item_index(row);
+        self.list.previous_sibling(index).is_some() && self.move_item_up(row).is_some()
+    }
+
+    /// Swaps the item at the given `row` along with all of its children with
+    /// its next sibling, like [`move_item_down`](#method.move_item_down)
+    /// does, returning whether there was such a sibling.
+    pub fn swap_with_next_sibling(&mut self, row: usize) -> bool {
+        let index = self.list.row_to_item_index(row);
+        self.list.next_sibling(index).is_some() && self.move_item_down(row).is_some()
+    }
+
+    /// Collapses the children of the given `row`.
+    ///
+    /// In case one of the children was focused, the focus moves to `row`.
+    pub fn collapse_item(&mut self, row: usize) {
+        self.set_collapsed(row, true);
+    }
+
+    /// Expands the children of the given `row`.
+    pub fn expand_item(&mut self, row: usize) {
+        self.set_collapsed(row, false);
+    }
+
+    /// Collapses or expands the children of the given `row`.
+    ///
+    /// The focus stays on the focused item, or moves to `row` in case the
+    /// focused item is hidden by collapsing it.
+    pub fn set_collapsed(&mut self, row: usize, collapsed: bool) {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        self.list.set_collapsed(index, collapsed);
+        self.restore_focus(id);
+    }
+
+    /// Collapses or expands the children of the focused container item, the
+    /// same way pressing `<Enter>` does.
+    ///
+    /// This consults the callback set via
+    /// [`set_on_pre_collapse`](#method.set_on_pre_collapse) and returns an
+    /// `EventResult` holding the callbacks set via
+    /// [`set_on_collapse`](#method.set_on_collapse), which should be processed
+    /// by the caller.
+    ///
+    /// `None` is returned in case no item is focused, or the focused item is
+    /// either disabled or not a container.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// # let mut siv = Cursive::new();
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_container_item("1".to_string(), Placement::After, 0);
+    ///
+    /// if let Some(result) = tree.toggle_focused() {
+    ///     result.process(&mut siv);
+    /// }
+    /// # }
+    /// ```
+    pub fn toggle_focused(&mut self) -> Option<EventResult> {
+        let index = self.list.row_to_item_index(self.row()?);
+        if self.list.is_container_item(index) && self.is_item_enabled(self.focus) {
+            Some(self.toggle_collapsed())
+        } else {
+            None
+        }
+    }
+
+    /// Collapses or expands the children of the given `row`.
+    ///
+    /// Chained variant.
+    pub fn collapsed(self, row: usize, collapsed: bool) -> Self {
+        self.with(|t| t.set_collapsed(row, collapsed))
+    }
+
+    /// Expands all container items within the tree.
+    pub fn expand_all(&mut self) {
+        let id = self.focused_id();
+        self.list.set_all_collapsed(false);
+        self.restore_focus(id);
+    }
+
+    /// Expands all container items within the tree.
+    ///
+    /// Chained variant.
+    pub fn expanded_all(self) -> Self {
+        self.with(|t| t.expand_all())
+    }
+
+    /// Collapses all container items within the tree.
+    ///
+    /// In case a nested item was focused, the focus moves to its top level
+    /// ancestor.
+    pub fn collapse_all(&mut self) {
+        let id = self.focused_id();
+        self.list.set_all_collapsed(true);
+        self.restore_focus(id);
+    }
+
+    /// Collapses all container items within the tree.
+    ///
+    /// Chained variant.
+    pub fn collapsed_all(self) -> Self {
+        self.with(|t| t.collapse_all())
+    }
+
+    /// Expands the item at the given `row` along with all container items
+    /// within its subtree.
+    ///
+    /// Children which were not loaded yet by the
+    /// [lazy loader](#method.set_lazy_loader) stay unloaded. Pressing `*`
+    /// does the same for the focused item, or collapses its subtree in case
+    /// it is fully expanded already.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::from_level_vec(vec![
+    ///     (0, true, "a".to_string()),
+    ///     (1, true, "b".to_string()),
+    ///     (2, false, "c".to_string()),
+    ///     (0, true, "d".to_string()),
+    ///     (1, false, "e".to_string()),
+    /// ]);
+    ///
+    /// tree.expand_descendants(0);
+    /// assert_eq!(tree.visible_height(), 4);
+    ///
+    /// tree.collapse_descendants(0);
+    /// assert_eq!(tree.visible_height(), 2);
+    /// # }
+    /// ```
+    pub fn expand_descendants(&mut self, row: usize) {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        self.list.set_subtree_collapsed(index, false);
+        self.restore_focus(id);
+    }
+
+    /// Collapses the item at the given `row` along with all container items
+    /// within its subtree.
+    ///
+    /// In case one of the descendants was focused, the focus moves to `row`.
+    ///
+    /// See [`expand_descendants`](#method.expand_descendants) for an example.
+    pub fn collapse_descendants(&mut self, row: usize) {
+        let id = self.focused_id();
+        let index = self.list.row_to_item_index(row);
+        self.list.set_subtree_collapsed(index, true);
+        self.restore_focus(id);
+    }
+
+    /// Limits the number of levels which are visible at once, e.g. `Some(3)`
+    /// only ever shows the top three levels of the tree.
+    ///
+    /// Items on the deepest visible level are collapsed and cannot be
+    /// expanded, neither by the user nor programmatically, while the limit
+    /// is in place. Unlike collapsing items individually, the limit also
+    /// applies to items which are inserted or moved later on; it is enforced
+    /// again before the tree is laid out. Items collapsed due to the limit
+    /// are expanded again once it is lifted. `None` removes the limit.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("1".to_string(), Placement::After, 0);
+    /// tree.insert_item("1.1".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("1.1.1".to_string(), Placement::LastChild, 1);
+    ///
+    /// tree.set_max_visible_depth(Some(2));
+    /// assert_eq!(tree.visible_height(), 2);
+    ///
+    /// tree.set_max_visible_depth(None);
+    /// assert_eq!(tree.visible_height(), 3);
+    /// # }
+    /// ```
+    pub fn set_max_visible_depth(&mut self, depth: Option<usize>) {
+        let id = self.focused_id();
+        self.list.set_max_depth(depth);
+        self.restore_focus(id);
+    }
+
+    /// Limits the number of levels which are visible at once.
+    ///
+    /// Chainable variant.
+    pub fn max_visible_depth(self, depth: Option<usize>) -> Self {
+        self.with(|t| t.set_max_visible_depth(depth))
+    }
+
+    /// Expands all collapsed parents of the item at the given item `index`,
+    /// so that it becomes visible, and returns the visual row it now occupies.
+    ///
+    /// Item indices are the positions reported by
+    /// [`iter_all`](#method.iter_all), which include items hidden within
+    /// collapsed parents.
+    ///
+    /// `None` is returned in case no item exists at `index` or it is beyond
+    /// the [visible depth](#method.set_max_visible_depth).
+    pub fn expand_to(&mut self, index: usize) -> Option<usize> {
+        self.list.expand_to(index)
+    }
+
+    /// Returns the item index of the given `row`.
+    ///
+    /// Unlike rows, item indices are not affected by collapsing or expanding
+    /// items, but do shift when items are inserted or removed; see
+    /// [`handle`](#method.handle) for references which stay valid across
+    /// such changes.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn row_to_index(&self, row: usize) -> Option<usize> {
+        if row < self.list.height() {
+            Some(self.list.row_to_item_index(row))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the visual row of the item at the given item `index`.
+    ///
+    /// `None` is returned in case no item exists at `index` or it is hidden
+    /// within a collapsed parent.
+    pub fn index_to_row(&self, index: usize) -> Option<usize> {
+        if self.list.is_visible(index) {
+            Some(self.list.item_index_to_row(index))
+        } else {
+            None
+        }
+    }
+
+    /// Select item `n` rows up from the one currently selected.
+    ///
+    /// Disabled rows are skipped over.
+    pub fn focus_up(&mut self, n: usize) {
+        let target = self.focus.saturating_sub(n);
+        self.focus = (0..=target)
+            .rev()
+            .chain(target + 1..self.focus)
+            .find(|&row| self.is_item_enabled(row))
+            .unwrap_or(self.focus);
+    }
+
+    /// Select item `n` rows down from the one currently selected.
+    ///
+    /// Disabled rows are skipped over.
+    pub fn focus_down(&mut self, n: usize) {
+        let height = self.list.height();
+        let target = cmp::min(self.focus + n, height.saturating_sub(1));
+        self.focus = (target..height)
+            .chain((self.focus + 1..target).rev())
+            .find(|&row| self.is_item_enabled(row))
+            .unwrap_or(self.focus);
+    }
+
+    /// Selects the next enabled sibling of the focused item, skipping over
+    /// all of its descendants, and returns the selected row.
+    ///
+    /// The focus stays unchanged in case there is no such sibling. Siblings
+    /// hidden by the [filter](#method.set_filter) are skipped. Pressing
+    /// `<Shift+Down>` or `<Ctrl+Down>` does the same.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::from_level_vec(vec![
+    ///     (0, false, "a".to_string()),
+    ///     (1, false, "b".to_string()),
+    ///     (1, false, "c".to_string()),
+    ///     (0, false, "d".to_string()),
+    /// ]);
+    ///
+    /// assert_eq!(tree.select_next_sibling(), Some(3));
+    /// assert_eq!(tree.select_next_sibling(), Some(3));
+    /// assert_eq!(tree.select_prev_sibling(), Some(0));
+    /// # }
+    /// ```
+    pub fn select_next_sibling(&mut self) -> Option<usize> {
+        if !self.deselected {
+            self.focus_sibling(TreeList::next_sibling);
+        }
+        self.row()
+    }
+
+    /// Selects the previous enabled sibling of the focused item and returns
+    /// the selected row.
+    ///
+    /// The focus stays unchanged in case there is no such sibling. Siblings
+    /// hidden by the [filter](#method.set_filter) are skipped. Pressing
+    /// `<Shift+Up>` or `<Ctrl+Up>` does the same.
+    pub fn select_prev_sibling(&mut self) -> Option<usize> {
+        if !self.deselected {
+            self.focus_sibling(TreeList::previous_sibling);
+        }
+        self.row()
+    }
+
+    /// Enables or disables the item at the given `row`.
+    ///
+    /// Disabled items are drawn greyed out and are skipped when navigating
+    /// the tree; they can neither be submitted, collapsed nor expanded by the
+    /// user.
+    pub fn set_item_enabled(&mut self, row: usize, enabled: bool) {
+        let index = self.list.row_to_item_index(row);
+        self.list.set_enabled(index, enabled);
+    }
+
+    /// Returns `true` if the item at the given `row` is enabled.
+    ///
+    /// `false` is returned in case the specified `row` does not visually
+    /// exist.
+    pub fn is_item_enabled(&self, row: usize) -> bool {
+        let index = self.list.row_to_item_index(row);
+        self.list.is_enabled(index)
+    }
+
+    /// Returns the row of the parent of the item at the given `row`.
+    ///
+    /// `None` is returned for top level items and in case the specified `row`
+    /// does not visually exist.
+    ///
+    /// This is the same as [`item_parent`](#method.item_parent).
+    pub fn parent_row(&self, row: usize) -> Option<usize> {
+        self.item_parent(row)
+    }
+
+    /// Returns position of the parent of the item located in `row`.
+    ///
+    /// `None` is returned if `row` is not currenlty visible or if the item has no ancestors.
+    pub fn item_parent(&self, row: usize) -> Option<usize> {
+        let item_index = self.list.row_to_item_index(row);
+        let parent_index = self.list.item_parent_index(item_index)?;
+        Some(self.list.item_index_to_row(parent_index))
+    }
+
+    /// Returns an iterator over the rows and values of all parents of the
+    /// item at the given `row`, from its immediate parent up to the top level.
+    ///
+    /// The iterator is empty for top level items and in case the specified
+    /// `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("usr".to_string(), Placement::After, 0);
+    /// tree.insert_item("share".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("doc".to_string(), Placement::LastChild, 1);
+    ///
+    /// let path: Vec<&str> = tree.ancestors(2).map(|(_, name)| name.as_str()).collect();
+    /// assert_eq!(path.join("/"), "share/usr");
+    /// # }
+    /// ```
+    pub fn ancestors(&self, row: usize) -> impl Iterator<Item = (usize, &T)> + '_ {
+        let first = if row < self.list.height() {
+            self.list
+                .item_parent_index(self.list.row_to_item_index(row))
+        } else {
+            None
+        };
+
+        iter::successors(first, move |&index| self.list.item_parent_index(index)).map(
+            move |index| {
+                (
+                    self.list.item_index_to_row(index),
+                    self.list.items()[index].value(),
+                )
+            },
+        )
+    }
+
+    /// Returns the item indices of all parents of the item at the given
+    /// `row` from the top level downwards, followed by the index of the item
+    /// itself.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn item_path(&self, row: usize) -> Option<Vec<usize>> {
+        let index = self.list.row_to_item_index(row);
+        self.list.get(index)?;
+
+        let mut path = vec![index];
+        while let Some(parent) = self.list.item_parent_index(path[path.len() - 1]) {
+            path.push(parent);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Returns the items along the [path](#method.item_path) of the given
+    /// `row`, joined by `separator`.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("Project".to_string(), Placement::After, 0);
+    /// tree.insert_item("src".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("lib.rs".to_string(), Placement::LastChild, 1);
+    ///
+    /// assert_eq!(
+    ///     tree.path_string(2, " ▸ "),
+    ///     Some("Project ▸ src ▸ lib.rs".to_string())
+    /// );
+    /// # }
+    /// ```
+    pub fn path_string(&self, row: usize, separator: &str) -> Option<String> {
+        let path: Vec<String> = self
+            .path_to(row)?
+            .into_iter()
+            .map(|item| format!("{}", item))
+            .collect();
+        Some(path.join(separator))
+    }
+
+    /// Returns the items along the [path](#method.item_path) of the given
+    /// `row`, from the top level down to the item itself.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("usr".to_string(), Placement::After, 0);
+    /// tree.insert_item("share".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("doc".to_string(), Placement::LastChild, 1);
+    ///
+    /// assert_eq!(
+    ///     tree.path_to(2),
+    ///     Some(vec![&"usr".to_string(), &"share".to_string(), &"doc".to_string()])
+    /// );
+    /// assert_eq!(tree.path_to(3), None);
+    /// # }
+    /// ```
+    pub fn path_to(&self, row: usize) -> Option<Vec<&T>> {
+        let items = self.list.items();
+        Some(
+            self.item_path(row)?
+                .into_iter()
+                .map(|index| items[index].value())
+                .collect(),
+        )
+    }
+
+    /// Returns the rows of the immediate children of the item at the given
+    /// `row`.
+    ///
+    /// Children hidden by a collapsed state are not included, see
+    /// [`item_children_indices`](#method.item_children_indices) for a
+    /// variant which includes them.
+    ///
+    /// An empty vector is returned for items without children and in case
+    /// the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("root".to_string(), Placement::After, 0);
+    /// tree.insert_item("a".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("a1".to_string(), Placement::LastChild, 1);
+    /// tree.insert_item("b".to_string(), Placement::LastChild, 0);
+    ///
+    /// assert_eq!(tree.item_children(0), vec![1, 3]);
+    ///
+    /// tree.collapse_item(1);
+    /// assert_eq!(tree.item_children(0), vec![1, 2]);
+    /// assert!(tree.item_children(1).is_empty());
+    /// # }
+    /// ```
+    pub fn item_children(&self, row: usize) -> Vec<usize> {
+        let index = self.list.row_to_item_index(row);
+        if self.list.is_collapsed(index) != Some(false) {
+            return Vec::new();
+        }
+
+        let items = self.list.items();
+        let mut child_row = row + 1;
+        self.list
+            .children_indices(index)
+            .into_iter()
+            .map(|child| {
+                let row = child_row;
+                child_row += items[child].height();
+                row
+            })
+            .collect()
+    }
+
+    /// Returns the rows of the immediate children of the item at the given
+    /// `row`, same as [`item_children`](#method.item_children).
+    #[doc(hidden)]
+    pub fn child_rows(&self, row: usize) -> Vec<usize> {
+        self.item_children(row)
+    }
+
+    /// Returns the item indices of the immediate children of the item at
+    /// the given `row`, including children hidden by a collapsed state.
+    ///
+    /// An empty vector is returned in case the specified `row` does not
+    /// visually exist.
+    pub fn item_children_indices(&self, row: usize) -> Vec<usize> {
+        let index = self.list.row_to_item_index(row);
+        self.list.children_indices(index)
+    }
+
+    /// Returns the number of immediate children of the item at the given
+    /// `row`, including children hidden by a collapsed state.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    pub fn children_count(&self, row: usize) -> Option<usize> {
+        let index = self.list.row_to_item_index(row);
+        self.list.get(index)?;
+        Some(self.list.children_indices(index).len())
+    }
+
+    /// Returns the total number of items within the subtree of the item at
+    /// the given `row`, excluding the item itself.
+    ///
+    /// `None` is returned in case the specified `row` does not visually exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<String>::new();
+    /// tree.insert_item("root".to_string(), Placement::After, 0);
+    /// tree.insert_item("child".to_string(), Placement::LastChild, 0);
+    /// tree.insert_item("grandchild".to_string(), Placement::LastChild, 1);
+    /// tree.collapse_item(0);
+    ///
+    /// assert_eq!(tree.children_count(0), Some(1));
+    /// assert_eq!(tree.descendant_count(0), Some(2));
+    /// assert_eq!(tree.max_depth(), 3);
+    /// # }
+    /// ```
+    pub fn descendant_count(&self, row: usize) -> Option<usize> {
+        let index = self.list.row_to_item_index(row);
+        self.list.get(index)?;
+        Some(self.list.get_children(index))
+    }
+
+    /// Returns an iterator over the item indices and values of all items
+    /// within the subtree of the item at the given `row`, excluding the item
+    /// itself, in top to bottom order.
+    ///
+    /// Unlike the parents yielded by [`ancestors`](#method.ancestors), which
+    /// are always visible, descendants may be hidden within collapsed
+    /// parents. They are therefore identified by their item index rather than
+    /// their row, which [`index_to_row`](#method.index_to_row) converts for
+    /// visible items.
+    ///
+    /// The iterator is empty in case the specified `row` does not visually
+    /// exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive_tree_view::{Placement, TreeView};
+    /// # fn main() {
+    /// let mut tree = TreeView::<usize>::new();
+    /// tree.insert_item(0, Placement::After, 0);
+    /// tree.insert_item(10, Placement::LastChild, 0);
+    /// tree.insert_item(20, Placement::LastChild, 1);
+    /// tree.insert_item(30, Placement::LastChild, 0);
+    /// tree.collapse_item(1);
+    ///
+    /// let size: usize = tree.descendants(0).map(|(_, size)| size).sum();
+    /// assert_eq!(size, 60);
+    /// # }
+    /// ```
+    pub fn descendants(&self, row: usize) -> impl Iterator<Item = (usize, &T)> + '_ {
+        let range = if row < self.list.height() {
+            let index = self.list.row_to_item_index(row);
+            index + 1..index + 1 + self.list.get_children(index)
+        } else {
+            0..0
+        };
+
+        let items = self.list.items();
+        range.map(move |index| (index, items[index].value()))
+    }
+
+    /// Returns the number of levels of the tree, including those hidden
+    /// within collapsed parents.
+    ///
+    /// A tree consisting only of top level items has a depth of `1`, an
+    /// empty tree a depth of `0`.
+    pub fn max_depth(&self) -> usize {
+        self.list
+            .items()
+            .iter()
+            .map(|item| item.level() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Computes the indent guides and row offsets of wrapped items for the
+    /// given `size`.
+    fn update_layout(&mut self, size: Vec2) {
+        self.apply_max_visible_depth();
+        self.apply_filter();
+        self.last_size = size;
+        self.row_offsets.clear();
+        self.guides = if self.indent_guides {
+            self.indent_guide_lines()
+        } else {
+            Vec::new()
+        };
+
+        if self.wrap {
+            let items = self.list.items();
+            let mut offsets = Vec::new();
+            let mut y = 0;
+            for index in self.list.visible_indices() {
+                offsets.push(y);
+                y += self.wrap_item(&items[index], size.x).len();
+            }
+            offsets.push(y);
+            self.row_offsets = offsets;
+        }
+    }
+
+    /// Returns the text of `item` split into the lines it occupies within a
+    /// view of the given `width`.
+    fn wrap_item(&self, item: &TreeNode<T>, width: usize) -> Vec<String> {
+        let width = self.wrap_width(item, width);
+        let lines: Vec<String> = match self.renderer {
+            Some(ref renderer) => {
+                let styled = renderer(item.value());
+                LinesIterator::new(&styled, width)
+                    .map(|row| {
+                        row.resolve_stream(&styled)
+                            .map(|span| span.content)
+                            .collect()
+                    })
+                    .collect()
+            }
+            None => {
+                let text = format!("{}", item.value());
+                make_lines(&text, width)
+                    .iter()
+                    .map(|line| text[line.start..line.end].to_string())
+                    .collect()
+            }
+        };
+
+        if lines.is_empty() {
+            vec![self.item_text(item)]
+        } else {
+            lines
+        }
+    }
+
+    /// Returns the number of columns the text of `item` is wrapped at within
+    /// a view of the given `width`.
+    fn wrap_width(&self, item: &TreeNode<T>, width: usize) -> usize {
+        cmp::max(width.saturating_sub(item.offset() + self.prefix_width()), 1)
+    }
+
+    /// Returns the plain text `item` is drawn with, i.e. the text of all
+    /// spans produced by the item renderer or its `Display` output.
+    fn item_text(&self, item: &TreeNode<T>) -> String {
+        match self.renderer {
+            Some(ref renderer) => {
+                let styled = renderer(item.value());
+                styled.spans().map(|span| span.content).collect()
+            }
+            None => format!("{}", item.value()),
+        }
+    }
+
+    /// Returns the position among the immediate children of `row` at which
+    /// `item` has to be inserted in order to keep them sorted by `cmp`.
+    fn sorted_position<F>(&self, row: usize, item: &T, cmp: F) -> usize
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let index = self.list.row_to_item_index(row);
+        let items = self.list.items();
+        self.list
+            .children_indices(index)
+            .into_iter()
+            .take_while(|&child| cmp(items[child].value(), item) != Ordering::Greater)
+            .count()
+    }
+
+    /// Inserts `item` after the last top level item.
+    fn push_root_node(&mut self, item: T, is_container: bool) -> usize {
+        let index = self.list.root_indices().last().copied().unwrap_or(0);
+        if is_container {
+            self.list
+                .insert_container_item(Placement::After, index, item);
+        } else {
+            self.list.insert_item(Placement::After, index, item);
+        }
+        self.list.item_index_to_row(self.list.len() - 1)
+    }
+
+    /// Inserts the nested items described by `builder` at the item `index`,
+    /// expanding all containers.
+    fn insert_builder(
+        &mut self,
+        placement: Placement,
+        index: usize,
+        builder: TreeBuilder<T>,
+    ) -> Option<usize> {
+        let (value, children) = match builder {
+            TreeBuilder::Leaf(value) => return self.list.insert_item(placement, index, value),
+            TreeBuilder::Node(value, children) => (value, children),
+        };
+
+        let id = self.list.next_id();
+        let row = self.list.insert_container_item(placement, index, value);
+        let index = self.list.id_to_item_index(id)?;
+        self.list.set_collapsed(index, false);
+        for child in children {
+            self.insert_builder(Placement::LastChild, index, child);
+        }
+        row
+    }
+
+    /// Returns the id of the currently focused item.
+    fn focused_id(&self) -> Option<usize> {
+        let index = self.list.row_to_item_index(self.focus);
+        self.list.items().get(index).map(TreeNode::id)
+    }
+
+    /// Returns the ID of the selected item, taking deselection into account.
+    fn selected_id(&self) -> Option<usize> {
+        self.row().and_then(|_| self.focused_id())
+    }
+
+    /// Moves the focus to the item with the given `id`, or to its collapsed
+    /// parent in case it is hidden.
+    ///
+    /// The focus is only limited to the existing rows in case the item no
+    /// longer exists or is hidden by the filter along with all its parents.
+    fn restore_focus(&mut self, id: Option<usize>) {
+        match id.and_then(|id| self.list.id_to_item_index(id)) {
+            Some(index) => {
+                let visible = self.list.visible_ancestor(index);
+                self.focus = cmp::min(
+                    self.list.item_index_to_row(visible),
+                    self.list.height().saturating_sub(1),
+                );
+            }
+            None => self.focus = cmp::min(self.focus, self.list.height().saturating_sub(1)),
+        }
+    }
+
+    /// Returns the width of the widest item including its indentation, but
+    /// without the checkbox and symbol.
+    ///
+    /// The width is only measured again once items were added, removed or
+    /// borrowed mutably since the last call.
+    fn max_item_width(&mut self) -> usize {
+        let revision = self.list.revision();
+        match self.max_width {
+            Some((width, measured)) if measured == revision => width,
+            _ => {
+                let width = self
+                    .list
+                    .items()
+                    .iter()
+                    .map(|item| item.offset() + self.text_width(item))
+                    .max()
+                    .unwrap_or(0);
+
+                self.max_width = Some((width, revision));
+                width
+            }
+        }
+    }
+
+    /// Returns the display width of the text of `item`, taking the
+    /// [renderer](#method.set_item_renderer) into account.
+    fn text_width(&self, item: &TreeNode<T>) -> usize {
+        match self.renderer {
+            Some(ref renderer) => renderer(item.value()).width(),
+            None => item.width(),
+        }
+    }
+
+    /// Returns the width of the checkbox and symbol drawn in front of items.
+    fn prefix_width(&self) -> usize {
+        if self.checkboxes {
+            CHECKBOX_WIDTH + self.symbol_width
+        } else {
+            self.symbol_width
+        }
+    }
+
+    /// Returns the symbol drawn in front of `item`.
+    fn symbol(&self, item: &TreeNode<T>) -> &str {
+        if item.is_container() {
+            if item.is_collapsed() {
+                &self.collapsed_symbol
+            } else {
+                &self.expanded_symbol
+            }
+        } else {
+            &self.leaf_symbol
+        }
+    }
+
+    /// Collapses items which ended up beyond the visible depth limit since
+    /// it was set, keeping the focus on the same item or its parent.
+    fn apply_max_visible_depth(&mut self) {
+        let id = self.focused_id();
+        self.list.apply_max_depth();
+        self.restore_focus(id);
+    }
+
+    /// Applies the filter query to all items, including those inserted since
+    /// it was set, keeping the focus on the same item or its closest visible
+    /// ancestor.
+    fn apply_filter(&mut self) {
+        let id = self.focused_id();
+        match self.filter {
+            Some(ref query) => {
+                let query = query.to_lowercase();
+                self.list.set_filter(Some(&|value: &T| {
+                    fuzzy_match(&query, &format!("{}", value).to_lowercase())
+                }));
+            }
+            None => self.list.set_filter(None),
+        }
+        self.restore_focus(id);
+    }
+
+    /// Returns the number of rows `<PageUp>` and `<PageDown>` move the focus
+    /// by, i.e. the rows within the viewport minus one to keep some overlap.
+    ///
+    /// The height of the view itself is used until it was drawn, e.g. in
+    /// case it is not wrapped in a `ScrollView`.
+    fn page_size(&self) -> usize {
+        let (start, end) = self.viewport();
+        let rows = if end > start {
+            end - start
+        } else {
+            self.last_size.y
+        };
+        cmp::max(rows.saturating_sub(1), 1)
+    }
+
+    /// Collapses the focused container, or moves the focus to the parent of
+    /// the item in case it is a leaf or already collapsed.
+    ///
+    /// `None` is returned in case the focus moved, otherwise the result of
+    /// the event.
+    fn collapse_or_focus_parent(&mut self) -> Option<EventResult> {
+        if self.deselected {
+            return Some(EventResult::Ignored);
+        }
+
+        let index = self.list.row_to_item_index(self.focus);
+        if self.list.is_container_item(index) && !self.list.get_collapsed(index) {
+            if self.is_item_enabled(self.focus) {
+                return Some(self.submit());
+            }
+            return Some(EventResult::Ignored);
+        }
+
+        match self.item_parent(self.focus) {
+            Some(parent) if self.is_item_enabled(parent) => {
+                self.focus = parent;
+                None
+            }
+            _ => Some(EventResult::Ignored),
+        }
+    }
+
+    /// Expands the focused container, or moves the focus to its first child
+    /// in case it is already expanded.
+    ///
+    /// `None` is returned in case the focus moved, otherwise the result of
+    /// the event.
+    fn expand_or_focus_child(&mut self) -> Option<EventResult> {
+        if self.deselected {
+            return Some(EventResult::Ignored);
+        }
+
+        let index = self.list.row_to_item_index(self.focus);
+        if !self.list.is_container_item(index) {
+            Some(EventResult::Ignored)
+        } else if self.list.get_collapsed(index) {
+            if self.is_item_enabled(self.focus) {
+                return Some(self.submit());
+            }
+            Some(EventResult::Ignored)
+        } else if self.list.get_children(index) > 0 && self.is_item_enabled(self.focus + 1) {
+            self.focus += 1;
+            None
+        } else {
+            Some(EventResult::Ignored)
+        }
+    }
+
+    /// Moves the focus to the closest enabled sibling of the focused item in
+    /// the direction given by `step`, skipping siblings hidden by the filter,
+    /// and returns whether the focus moved.
+    fn focus_sibling<F>(&mut self, step: F) -> bool
+    where
+        F: Fn(&TreeList<T>, usize) -> Option<usize>,
+    {
+        let mut index = self.list.row_to_item_index(self.focus);
+        while let Some(sibling) = step(&self.list, index) {
+            if self.list.is_enabled(sibling) && self.list.is_visible(sibling) {
+                self.focus = self.list.item_index_to_row(sibling);
+                return true;
+            }
+            index = sibling;
+        }
+        false
+    }
+
+    /// Returns the guide lines drawn in front of the first and any further
+    /// lines of each visible row.
+    fn indent_guide_lines(&self) -> Vec<(String, String)> {
+        let items = self.list.items();
+        let levels: Vec<usize> = self
+            .list
+            .visible_indices()
+            .map(|index| items[index].level())
+            .collect();
+
+        // Whether each row is followed by a visible sibling, which excludes
+        // siblings hidden by a filter
+        let mut has_next = vec![false; levels.len()];
+        let mut seen: Vec<bool> = Vec::new();
+        for (row, &level) in levels.iter().enumerate().rev() {
+            has_next[row] = seen.get(level).copied().unwrap_or(false);
+            seen.resize(level, false);
+            seen.push(true);
+        }
+
+        // Whether the last visited item on each level is followed by a sibling
+        let mut open: Vec<bool> = Vec::new();
+        levels
+            .into_iter()
+            .zip(has_next)
+            .map(|(level, has_next)| {
+                open.truncate(level);
+
+                let mut first = String::new();
+                for &is_open in open.iter().skip(1) {
+                    first.push_str(if is_open { "│ " } else { "  " });
+                }
+
+                let mut rest = first.clone();
+                if level > 0 {
+                    first.push_str(if has_next { "├─" } else { "└─" });
+                    rest.push_str(if has_next { "│ " } else { "  " });
+                }
+
+                open.push(has_next);
+                (first, rest)
+            })
+            .collect()
+    }
+
+    /// Returns the vertical offset of the first line of `row`.
+    fn row_y(&self, row: usize) -> usize {
+        self.row_offsets.get(row).cloned().unwrap_or(row)
+    }
+
+    /// Returns the number of lines occupied by `row`.
+    fn row_lines(&self, row: usize) -> usize {
+        match (self.row_offsets.get(row), self.row_offsets.get(row + 1)) {
+            (Some(start), Some(end)) => end - start,
+            _ => 1,
+        }
+    }
+
+    /// Returns the row which occupies the line at the vertical offset `y`.
+    fn row_at(&self, y: usize) -> usize {
+        if self.row_offsets.is_empty() {
+            y
+        } else {
+            match self.row_offsets.binary_search(&y) {
+                Ok(row) => row,
+                Err(row) => row - 1,
+            }
+        }
+    }
+
+    /// Adds `c` to the search buffer and moves the focus to the next row
+    /// which matches the buffer, expanding its parents if enabled.
+    fn search(&mut self, c: char) {
+        let now = Instant::now();
+        let expired = match self.last_search_input {
+            Some(last) => now.duration_since(last) > SEARCH_TIMEOUT,
+            None => true,
+        };
+        if expired {
+            self.search_buffer.clear();
+        }
+        self.last_search_input = Some(now);
+        self.search_buffer.extend(c.to_lowercase());
+
+        // A new search starts after the focused row, while a continued search
+        // keeps the focused row in case it still matches
+        let skip = if self.search_buffer.chars().count() == 1 {
+            1
+        } else {
+            0
+        };
+
+        let items = self.list.items();
+        let matches = |index: usize| {
+            let text = format!("{}", items[index].value()).to_lowercase();
+            items[index].is_enabled()
+                && match self.typeahead {
+                    TypeaheadMode::Off => false,
+                    TypeaheadMode::Prefix => text.starts_with(&self.search_buffer),
+                    TypeaheadMode::Substring => text.contains(&self.search_buffer),
+                }
+        };
+
+        if self.typeahead_expand && !self.list.is_filtered() {
+            // Matches beyond the visible depth cannot be expanded to
+            let start = self.list.row_to_item_index(self.focus) + skip;
+            let found: Vec<usize> = (0..items.len())
+                .map(|offset| (start + offset) % items.len())
+                .filter(|&index| matches(index))
+                .collect();
+            if let Some(row) = found
+                .into_iter()
+                .find_map(|index| self.list.expand_to(index))
+            {
+                self.focus = row;
+            }
+        } else {
+            let start = self.focus + skip;
+            let rows: Vec<usize> = self.list.visible_indices().collect();
+            let found = (0..rows.len())
+                .map(|offset| (start + offset) % rows.len())
+                .find(|&row| matches(rows[row]));
+            if let Some(row) = found {
+                self.focus = row;
+            }
+        }
+    }
+
+    /// Loads the children of the container item via the lazy or async
+    /// loader, unless it already has children or was loaded before.
+    fn load_container(&mut self, index: usize, row: usize) {
+        if let (0, false, Some(loader)) = (
+            self.list.get_children(index),
+            self.list.is_loaded(index),
+            self.lazy_loader.clone(),
+        ) {
+            self.list.set_loaded(index, true);
+            let children = match self.list.get(index) {
+                Some(item) => loader.lock().unwrap().load_children(item),
+                None => Vec::new(),
+            };
+            for (value, is_container) in children {
+                if is_container {
+                    self.insert_container_item(value, Placement::LastChild, row);
+                } else {
+                    self.insert_item(value, Placement::LastChild, row);
+                }
+            }
+        } else if let (0, false, Some(loader)) = (
+            self.list.get_children(index),
+            self.list.is_loaded(index),
+            self.async_loader.clone(),
+        ) {
+            self.list.set_loaded(index, true);
+            if let Some(item) = self.list.items().get(index) {
+                let handle = ItemHandle(item.id());
+                let placeholder = {
+                    let mut loader = loader.lock().unwrap();
+                    loader.request_children(item.value(), handle);
+                    loader.placeholder(item.value())
+                };
+                self.list
+                    .insert_item(Placement::LastChild, index, placeholder);
+                self.list.set_enabled(index + 1, false);
+            }
+        }
+    }
+
+    /// Collapses or expands the focused container item.
+    fn toggle_collapsed(&mut self) -> EventResult {
+        let row = self.focus;
+        let index = self.list.row_to_item_index(row);
+        let collapsed = self.list.get_collapsed(index);
+
+        // Items on the deepest visible level cannot be expanded and filtered
+        // trees ignore the collapsed state
+        if (collapsed && self.list.exceeds_max_depth(index)) || self.list.is_filtered() {
+            return EventResult::Ignored;
+        }
+
+        if let (true, Some(cb)) = (collapsed, self.on_before_expand.clone()) {
+            if !cb(self, row) {
+                return EventResult::Consumed(None);
+            }
+        }
+
+        let children = self.list.get_children(index);
+
+        if let Some(ref cb) = self.on_pre_collapse {
+            if !cb(row, !collapsed, children) {
+                return EventResult::Consumed(None);
+            }
+        }
+
+        // Children are only loaded once the change was not cancelled
+        if collapsed {
+            self.load_container(index, row);
+        }
+
+        let children = self.list.get_children(index);
+
+        // Expanding an item in accordion mode collapses its expanded siblings
+        let siblings: Vec<usize> = if collapsed && self.accordion {
+            self.list
+                .sibling_indices(index)
+                .into_iter()
+                .filter(|&sibling| {
+                    sibling != index && self.list.is_collapsed(sibling) == Some(false)
+                })
+                .filter(|&sibling| self.list.is_container_item(sibling))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        for &sibling in &siblings {
+            self.list.set_collapsed(sibling, true);
+        }
+
+        self.list.set_collapsed(index, !collapsed);
+
+        // Collapsing siblings above the item moves it further up
+        let row = self.list.item_index_to_row(index);
+        self.focus = row;
+
+        let mut cb = None;
+        for sibling in siblings {
+            let sibling_row = self.list.item_index_to_row(sibling);
+            let sibling_children = self.list.get_children(sibling);
+            cb = Self::chain(
+                cb,
+                self.collapse_callbacks(sibling, sibling_row, true, sibling_children),
+            );
+        }
+
+        EventResult::Consumed(Self::chain(
+            cb,
+            self.collapse_callbacks(index, row, !collapsed, children),
+        ))
+    }
+
+    /// Expands the subtree of the focused container item, or collapses it in
+    /// case it is fully expanded already, invoking the callbacks and loaders
+    /// for the focused item only.
+    fn toggle_descendants(&mut self) -> EventResult {
+        let row = self.focus;
+        let index = self.list.row_to_item_index(row);
+        if !self.list.is_container_item(index) || self.list.is_filtered() {
+            return EventResult::Ignored;
+        }
+
+        let collapsed = self.list.is_subtree_expanded(index);
+        let expand = !collapsed && self.list.get_collapsed(index);
+        if let (true, Some(cb)) = (expand, self.on_before_expand.clone()) {
+            if !cb(self, row) {
+                return EventResult::Consumed(None);
+            }
+        }
+
+        let children = self.list.get_children(index);
+        if let Some(ref cb) = self.on_pre_collapse {
+            if !cb(row, collapsed, children) {
+                return EventResult::Consumed(None);
+            }
+        }
+
+        if expand {
+            self.load_container(index, row);
+        }
+
+        let children = self.list.get_children(index);
+        self.list.set_subtree_collapsed(index, collapsed);
+        let collapsed = self.list.get_collapsed(index);
+        EventResult::Consumed(self.collapse_callbacks(index, row, collapsed, children))
+    }
+
+    /// Returns the collapse callbacks for the item at `index` and `row`
+    /// which was just collapsed or expanded, chained into one.
+    fn collapse_callbacks(
+        &self,
+        index: usize,
+        row: usize,
+        collapsed: bool,
+        children: usize,
+    ) -> Option<Callback> {
+        let cb = self
+            .on_collapse
+            .clone()
+            .map(|cb| Callback::from_fn(move |s| cb(s, row, collapsed, children)));
+        let direction_cb = if collapsed {
+            self.on_collapse_only.clone()
+        } else {
+            self.on_expand.clone()
+        }
+        .map(|cb| Callback::from_fn(move |s| cb(s, row, children)));
+        let item_cb = self
+            .on_collapse_item
+            .as_ref()
+            .and_then(|cb| Some(cb(self.list.get(index)?, collapsed, children)));
+
+        Self::chain(Self::chain(cb, direction_cb), item_cb)
+    }
+
+    fn submit(&mut self) -> EventResult {
+        let row = self.focus;
+        let index = self.list.row_to_item_index(row);
+
+        if self.list.is_container_item(index) {
+            let result = self.toggle_collapsed();
+            return match self.on_submit_container.clone() {
+                Some(cb) => {
+                    // Toggling may move the container in accordion mode
+                    let row = self.focus;
+                    let cb = Some(Callback::from_fn(move |s| cb(s, row)));
+                    match result {
+                        EventResult::Consumed(toggled) => {
+                            EventResult::Consumed(Self::chain(toggled, cb))
+                        }
+                        EventResult::Ignored => EventResult::Consumed(cb),
+                    }
+                }
+                None => result,
+            };
+        } else {
+            let cb = self
+                .on_submit
+                .clone()
+                .map(|cb| Callback::from_fn(move |s| cb(s, row)));
+            let item_cb = self
+                .on_submit_item
+                .as_ref()
+                .and_then(|cb| Some(cb(self.list.get(index)?)));
+
+            if let Some(cb) = Self::chain(cb, item_cb) {
+                return EventResult::Consumed(Some(cb));
+            }
+        }
+
+        EventResult::Ignored
+    }
+
+    /// Returns the callback for reaching the given `edge`, if any.
+    fn edge_reached(&self, edge: Edge) -> EventResult {
+        match self.on_edge.clone() {
+            Some(cb) => EventResult::with_cb(move |s| cb(s, edge)),
+            None => EventResult::Ignored,
+        }
+    }
+
+    /// Returns the callbacks to be run once the given `row` was selected.
+    fn select_callback(&self, row: usize) -> Option<Callback> {
+        let cb = self
+            .on_select
+            .clone()
+            .map(|cb| Callback::from_fn(move |s| cb(s, row)));
+        let item_cb = self.on_select_item.as_ref().and_then(|cb| {
+            let index = self.list.row_to_item_index(row);
+            Some(cb(self.list.get(index)?))
+        });
+        Self::chain(cb, item_cb)
+    }
+
+    /// Combines two optional callbacks into one which runs both in order.
+    fn chain(first: Option<Callback>, second: Option<Callback>) -> Option<Callback> {
+        match (first, second) {
+            (Some(first), Some(second)) => Some(Callback::from_fn(move |s| {
+                first(s);
+                second(s);
+            })),
+            (first, second) => first.or(second),
+        }
+    }
+}
+
+impl<T: Display + Debug + Send + Sync + Clone + 'static> TreeView<T> {
+    /// Sets a callback to be used when `<Enter>` is pressed while a non
+    /// container item is selected, receiving the item itself instead of its
+    /// row.
+    ///
+    /// The callback is handed a clone of the item taken at the time of the
+    /// event. Use [`set_on_submit`](#method.set_on_submit) when the handler
+    /// needs to look up the item in the tree again; both callbacks are run
+    /// if set, the row based one first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_submit_item(|siv: &mut Cursive, item: &String| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_submit_item<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, &T) + Send + Sync + 'static,
+    {
+        let cb = Arc::new(cb);
+        self.on_submit_item = Some(Arc::new(move |item| {
+            let (cb, item) = (cb.clone(), item.clone());
+            Callback::from_fn(move |s| cb(s, &item))
+        }));
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed while a non
+    /// container item is selected, receiving the item itself instead of its
+    /// row.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.on_submit_item(|siv: &mut Cursive, item: &String| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn on_submit_item<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &T) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_submit_item(cb))
+    }
+
+    /// Sets a callback to be used when an item is selected, receiving the
+    /// item itself instead of its row.
+    ///
+    /// The callback is handed a clone of the item taken at the time of the
+    /// event and runs after the one set via
+    /// [`set_on_select`](#method.set_on_select), if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_select_item(|siv: &mut Cursive, item: &String| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_select_item<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, &T) + Send + Sync + 'static,
+    {
+        let cb = Arc::new(cb);
+        self.on_select_item = Some(Arc::new(move |item| {
+            let (cb, item) = (cb.clone(), item.clone());
+            Callback::from_fn(move |s| cb(s, &item))
+        }));
+    }
+
+    /// Sets a callback to be used when an item is selected, receiving the
+    /// item itself instead of its row.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.on_select_item(|siv: &mut Cursive, item: &String| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn on_select_item<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &T) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_select_item(cb))
+    }
+
+    /// Sets a callback to be used when an item has its children collapsed
+    /// or expanded, receiving the item itself instead of its row.
+    ///
+    /// The callback is handed a clone of the item taken at the time of the
+    /// event and runs after the one set via
+    /// [`set_on_collapse`](#method.set_on_collapse), if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.set_on_collapse_item(|siv: &mut Cursive, item: &String, is_collapsed: bool, children: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn set_on_collapse_item<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, &T, bool, usize) + Send + Sync + 'static,
+    {
+        let cb = Arc::new(cb);
+        self.on_collapse_item = Some(Arc::new(move |item, is_collapsed, children| {
+            let (cb, item) = (cb.clone(), item.clone());
+            Callback::from_fn(move |s| cb(s, &item, is_collapsed, children))
+        }));
+    }
+
+    /// Sets a callback to be used when an item has its children collapsed
+    /// or expanded, receiving the item itself instead of its row.
+    ///
+    /// Chainable variant.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate cursive;
+    /// # extern crate cursive_tree_view;
+    /// # use cursive::Cursive;
+    /// # use cursive_tree_view::TreeView;
+    /// # fn main() {
+    /// # let mut tree = TreeView::<String>::new();
+    /// tree.on_collapse_item(|siv: &mut Cursive, item: &String, is_collapsed: bool, children: usize| {
+    ///
+    /// });
+    /// # }
+    /// ```
+    pub fn on_collapse_item<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &T, bool, usize) + Send + Sync + 'static,
+    {
+        self.with(|t| t.set_on_collapse_item(cb))
+    }
+}
+
+/// Returns whether all characters of `query` appear in `text` in order.
+fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
+
+/// Shortens `text` to fit into `width` columns according to `truncation`.
+fn truncate(text: &str, width: usize, truncation: Truncate) -> String {
+    if truncation == Truncate::None || simple_prefix(text, usize::MAX).width <= width {
+        return text.to_string();
+    } else if width == 0 {
+        return String::new();
+    }
+
+    let available = width - 1;
+    match truncation {
+        Truncate::Middle => {
+            let start = simple_prefix(text, available - available / 2);
+            let end = simple_suffix(text, available / 2);
+            format!(
+                "{}…{}",
+                &text[..start.length],
+                &text[text.len() - end.length..]
+            )
+        }
+        _ => format!("{}…", &text[..simple_prefix(text, available).length]),
+    }
+}
+
+impl<T: Display + Send + Sync + Debug + 'static> View for TreeView<T> {
+    fn draw(&self, printer: &Printer<'_, '_>) {
+        *self.viewport.lock().unwrap() = (
+            printer.content_offset.y,
+            printer.content_offset.y + printer.output_size.y,
+        );
+
+        let items = self.list.items();
+
+        for (i, index) in self.list.visible_indices().enumerate() {
+            let printer = printer.offset((0, self.row_y(i)));
+            let item = &items[index];
+
+            let item_style = self
+                .item_style
+                .as_ref()
+                .map(|cb| cb(item.value(), i, item.is_selected()));
+
+            let row_color = match self.alternate_row_color {
+                Some(color) if i % 2 == 1 => color,
+                _ => self.row_color,
+            };
+
+            let mut style = match item_style {
+                Some(style) => style,
+                None if !item.is_enabled() => ColorStyle {
+                    front: ColorStyle::secondary().front,
+                    ..row_color
+                }
+                .into(),
+                None if self.multi_select && item.is_selected() => ColorStyle {
+                    front: ColorStyle::title_primary().front,
+                    ..row_color
+                }
+                .into(),
+                None => row_color.into(),
+            };
+
+            let focused = i == self.focus && !self.deselected;
+            if focused {
+                style.color = if self.enabled && printer.focused {
+                    self.selection_style
+                } else {
+                    self.inactive_selection_style
+                };
+            }
+
+            // The indentation, checkbox and symbol are drawn on top of the
+            // highlight or stripe of the full row
+            let highlight = focused && self.full_row_highlight;
+            let fill = if highlight {
+                Some(style)
+            } else if self.alternate_row_color.is_some() {
+                Some(row_color.into())
+            } else {
+                None
+            };
+            let row_style = fill.unwrap_or_default();
+            if let Some(fill) = fill {
+                printer.with_style(fill, |printer| {
+                    for y in 0..self.row_lines(i) {
+                        printer.print_hline((0, y), printer.size.x, " ");
+                    }
+                });
+            }
+
+            if let Some((first, rest)) = self.guides.get(i).filter(|_| self.indent_guides) {
+                // Guides are dimmed, unless they are part of the highlight
+                let mut guide_style = row_style;
+                if !highlight {
+                    guide_style.color.front = ColorStyle::secondary().front;
+                }
+                printer.with_style(guide_style, |printer| {
+                    for y in 0..self.row_lines(i) {
+                        printer.print((0, y), if y == 0 { first } else { rest });
+                    }
+                });
+            }
+
+            let mut x = item.offset();
+            if self.checkboxes {
+                let checkbox = match self.list.check_state(index) {
+                    Some(CheckState::Checked) => "[x]",
+                    Some(CheckState::Partial) => "[~]",
+                    _ => "[ ]",
+                };
+                printer.with_style(row_style, |printer| {
+                    printer.print((x, 0), checkbox);
+                });
+                x += CHECKBOX_WIDTH;
+            }
+            let symbol_style = if highlight {
+                style
+            } else {
+                item_style.unwrap_or(row_style)
+            };
+            printer.with_style(symbol_style, |printer| {
+                printer.print((x, 0), self.symbol(item));
+            });
+
+            let lines = if self.wrap {
+                if let (Some(renderer), false) = (&self.renderer, focused) {
+                    let styled = renderer(item.value());
+                    let width = self.wrap_width(item, self.last_size.x);
+                    printer.with_style(style, |printer| {
+                        for (y, row) in LinesIterator::new(&styled, width).enumerate() {
+                            let mut x = item.offset() + self.prefix_width();
+                            for span in row.resolve_stream(&styled) {
+                                printer.with_style(*span.attr, |printer| {
+                                    printer.print((x, y), span.content);
+                                });
+                                x += span.width;
+                            }
+                        }
+                    });
+                    continue;
+                }
+                self.wrap_item(item, self.last_size.x)
+            } else {
+                let width = self
+                    .last_size
+                    .x
+                    .saturating_sub(item.offset() + self.prefix_width());
+
+                let text = match self.renderer {
+                    Some(ref renderer) => {
+                        let styled = renderer(item.value());
+                        let fits = self.truncation == Truncate::None || styled.width() <= width;
+                        if fits && !focused {
+                            printer.with_style(style, |printer| {
+                                printer.print_styled(
+                                    (item.offset() + self.prefix_width(), 0),
+                                    &styled,
+                                );
+                            });
+                            continue;
+                        }
+                        styled.spans().map(|span| span.content).collect()
+                    }
+                    None => format!("{}", item.value()),
+                };
+                vec![truncate(&text, width, self.truncation)]
+            };
+
+            printer.with_style(style, |printer| {
+                for (y, line) in lines.iter().enumerate() {
+                    printer.print((item.offset() + self.prefix_width(), y), line);
+                }
+            });
+        }
+    }
+
+    fn required_size(&mut self, req: Vec2) -> Vec2 {
+        self.apply_max_visible_depth();
+        self.apply_filter();
+
+        let w = if self.list.is_empty() {
+            0
+        } else {
+            self.max_item_width() + self.prefix_width()
+        };
+
+        if self.wrap && w > req.x {
+            let items = self.list.items();
+            let h = self
+                .list
+                .visible_indices()
+                .map(|index| self.wrap_item(&items[index], req.x).len())
+                .sum();
+
+            (req.x, h).into()
+        } else {
+            let h = self.list.height();
+
+            (w, h).into()
+        }
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        self.update_layout(size);
+    }
+
+    fn take_focus(&mut self, source: Direction) -> Result<EventResult, CannotFocus> {
+        if !self.enabled || self.is_empty() {
+            return Err(CannotFocus);
+        }
+
+        // Entering from above or below selects the first or last enabled row,
+        // otherwise e.g. via Tab the selection is kept and scrolled into view
+        // by the parent via `important_area`
+        let last_row = self.row();
+        match source {
+            Direction::Abs(Absolute::Up) => {
+                self.focus = 0;
+                self.focus_down(0);
+            }
+            Direction::Abs(Absolute::Down) => {
+                self.focus = self.list.height().saturating_sub(1);
+                self.focus_up(0);
+            }
+            _ => return Ok(EventResult::consumed()),
+        }
+        self.deselected = false;
+
+        match self.row() {
+            Some(row) if Some(row) != last_row => {
+                self.notified_id = self.selected_id();
+                Ok(EventResult::Consumed(self.select_callback(row)))
+            }
+            _ => Ok(EventResult::consumed()),
+        }
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if !self.enabled {
+            return EventResult::Ignored;
+        }
+
+        // Empty trees neither reach an edge nor wrap around
+        if let (true, Event::Key(_) | Event::Shift(_) | Event::Ctrl(_) | Event::Char(_)) =
+            (self.is_empty(), &event)
+        {
+            return EventResult::Ignored;
+        }
+
+        if let Event::Key(_) | Event::Mouse { .. } = event {
+            self.search_buffer.clear();
+        }
+
+        let last_focus = self.focus;
+        let last_row = self.row();
+        match event {
+            Event::Key(Key::Up)
+            | Event::Key(Key::Down)
+            | Event::Key(Key::PageUp)
+            | Event::Key(Key::PageDown)
+            | Event::Key(Key::Home)
+            | Event::Key(Key::End)
+                if self.deselected =>
+            {
+                self.deselected = false;
+                self.focus = 0;
+                self.focus_down(0);
+            }
+            Event::Key(Key::Esc) if self.allow_deselect && !self.deselected => {
+                self.deselected = true;
+                return EventResult::consumed();
+            }
+            // Wrap around in case there is no enabled row left to move to
+            Event::Key(Key::Up)
+                if self.wrap_navigation
+                    && !(0..self.focus).any(|row| self.is_item_enabled(row)) =>
+            {
+                self.focus = self.list.height().saturating_sub(1);
+                self.focus_up(0);
+            }
+            Event::Key(Key::Down)
+                if self.wrap_navigation
+                    && !(self.focus + 1..self.list.height())
+                        .any(|row| self.is_item_enabled(row)) =>
+            {
+                self.focus = 0;
+                self.focus_down(0);
+            }
+            Event::Key(Key::Up) if self.focus > 0 => {
+                self.focus_up(1);
+            }
+            Event::Key(Key::Down) if self.focus + 1 < self.list.height() => {
+                self.focus_down(1);
+            }
+            Event::Key(Key::Up) => return self.edge_reached(Edge::Top),
+            Event::Key(Key::Down) => return self.edge_reached(Edge::Bottom),
+            Event::Key(Key::PageUp) => {
+                self.focus_up(self.page_size());
+            }
+            Event::Key(Key::PageDown) => {
+                self.focus_down(self.page_size());
+            }
+            Event::Key(Key::Home) => {
+                self.focus_up(self.focus);
+            }
+            Event::Key(Key::End) => {
+                self.focus_down(self.list.height());
+            }
+            Event::Shift(Key::Down) | Event::Ctrl(Key::Down) if !self.deselected => {
+                if !self.focus_sibling(TreeList::next_sibling) {
+                    return EventResult::Ignored;
+                }
+            }
+            Event::Shift(Key::Up) | Event::Ctrl(Key::Up) if !self.deselected => {
+                if !self.focus_sibling(TreeList::previous_sibling) {
+                    return EventResult::Ignored;
+                }
+            }
+            Event::Key(Key::Enter) => {
+                if !self.deselected && self.is_item_enabled(self.focus) {
+                    return self.submit();
+                }
+            }
+            Event::Char(' ') if self.checkboxes => {
+                if !self.deselected && self.is_item_enabled(self.focus) {
+                    let index = self.list.row_to_item_index(self.focus);
+                    let checked = self.list.check_state(index) != Some(CheckState::Checked);
+                    self.list.set_checked(index, checked);
+                    return match self.on_check.clone() {
+                        Some(cb) => {
+                            let row = self.focus;
+                            EventResult::with_cb(move |siv| cb(siv, row, checked))
+                        }
+                        None => EventResult::consumed(),
+                    };
+                }
+            }
+            Event::Char(' ') if self.multi_select => {
+                if !self.deselected && self.is_item_enabled(self.focus) {
+                    let index = self.list.row_to_item_index(self.focus);
+                    self.list.toggle_selected(index);
+                    return EventResult::consumed();
+                }
+            }
+            Event::Char('h') if self.vim_keys => {
+                if let Some(result) = self.collapse_or_focus_parent() {
+                    return result;
+                }
+            }
+            Event::Key(Key::Left) if self.arrow_keys => {
+                if let Some(result) = self.collapse_or_focus_parent() {
+                    return result;
+                }
+            }
+            Event::Char('l') if self.vim_keys => {
+                if let Some(result) = self.expand_or_focus_child() {
+                    return result;
+                }
+            }
+            Event::Key(Key::Right) if self.arrow_keys => {
+                if let Some(result) = self.expand_or_focus_child() {
+                    return result;
+                }
+            }
+            Event::Char('*') if self.search_query().is_empty() => {
+                if self.deselected || !self.is_item_enabled(self.focus) {
+                    return EventResult::Ignored;
+                }
+                return self.toggle_descendants();
+            }
+            Event::Char(c) if self.typeahead != TypeaheadMode::Off => {
+                self.search(c);
+                if self.focus == last_focus {
+                    return EventResult::consumed();
+                }
+                self.deselected = false;
+            }
+            Event::Mouse {
+                position,
+                offset,
+                event: MouseEvent::Press(btn),
+            } => {
+                if let Some(position) = position.checked_sub(offset) {
+                    match self.row_at(position.y) {
+                        row if !self.is_item_enabled(row) => return EventResult::Ignored,
+                        row if row == self.focus
+                            && btn == MouseButton::Left
+                            && !self.deselected =>
+                        {
+                            return self.submit()
+                        }
+                        row => {
+                            self.focus = row;
+                            self.deselected = false;
+                        }
+                    }
+                }
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        let row = self.row();
+
+        if let (Some(last_row), Some(cb)) = (last_row, &self.on_leave) {
+            if row != Some(last_row) && !cb(last_row) {
+                self.focus = last_focus;
+                return EventResult::consumed();
+            }
+        }
+
+        match row {
+            Some(row) if Some(row) != last_row => {
+                self.notified_id = self.selected_id();
+                EventResult::Consumed(self.select_callback(row))
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn important_area(&self, size: Vec2) -> Rect {
+        Rect::from_size(
+            (0, self.row_y(self.focus)),
+            (size.x, self.row_lines(self.focus)),
+        )
+    }
+}
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod test {
+    use cursive::buffer::PrintBuffer;
+    use cursive::theme::{ColorPair, ColorStyle};
+    use cursive::view::View;
+    use cursive::Vec2;
+
+    /// Lays out and draws `view` into a buffer of the given `size`.
+    fn draw<V: View>(view: &mut V, size: Vec2) -> PrintBuffer {
+        use cursive::theme::Theme;
+        use cursive::Printer;
+
+        let theme = Theme::default();
+        let mut buffer = PrintBuffer::new();
+        buffer.resize(size);
+
+        // The lock type of the printer is not exported by cursive
+        let buffer = From::from(buffer);
+        view.layout(size);
+        view.draw(&Printer::new(size, &theme, &buffer));
+        buffer.into_inner()
+    }
+
+    /// Resolves `style` against the palette of the theme used by `draw`.
+    fn colors(style: ColorStyle) -> ColorPair {
+        use cursive::theme::Theme;
+
+        style.resolve(&Theme::default().palette, ColorPair::terminal_default())
+    }
+
+    /// Returns the text of each line of `buffer` with trailing spaces
+    /// removed.
+    fn lines(buffer: &PrintBuffer) -> Vec<String> {
+        buffer
+            .rows()
+            .map(|row| {
+                let line: String = row
+                    .iter()
+                    .map(|cell| cell.as_ref().map_or(" ", |cell| cell.text()))
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_select() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new().multi_select(true);
+        tree.insert_item("1".to_string(), Placement::LastChild, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::LastChild, 1);
+        tree.insert_item("4".to_string(), Placement::After, 0);
+
+        tree.on_event(Event::Char(' '));
+        tree.on_event(Event::Key(Key::Down));
+        tree.on_event(Event::Key(Key::Down));
+        tree.on_event(Event::Char(' '));
+        tree.on_event(Event::Key(Key::End));
+        tree.on_event(Event::Char(' '));
+        assert_eq!(tree.selected_rows(), vec![0, 2, 3]);
+
+        tree.collapse_item(1);
+        assert_eq!(tree.selected_rows(), vec![0, 2]);
+
+        tree.expand_item(1);
+        tree.set_selected_row(0);
+        tree.on_event(Event::Char(' '));
+        assert_eq!(tree.selected_rows(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_pre_collapse_veto() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new()
+            .on_pre_collapse(|_, is_collapsed, children| is_collapsed || children > 0);
+        tree.insert_item("1".to_string(), Placement::LastChild, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_container_item("3".to_string(), Placement::After, 0);
+
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(tree.is_collapsed(0), Some(true));
+
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+
+        tree.set_selected_row(2);
+        assert!(tree.on_event(Event::Key(Key::Enter)).is_consumed());
+        assert_eq!(tree.is_collapsed(2), Some(true));
+    }
+
+    #[test]
+    fn test_find() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("a".to_string(), Placement::LastChild, 0);
+        tree.insert_item("b".to_string(), Placement::LastChild, 0);
+        tree.insert_item("ab".to_string(), Placement::After, 0);
+        tree.collapse_item(0);
+
+        assert_eq!(tree.find_rows(|item| item.contains('a')), vec![0, 1]);
+        assert_eq!(tree.find_first(|item| item.contains('b')), Some(1));
+        assert_eq!(tree.find_indices(|item| item.contains('b')), vec![1, 2]);
+
+        assert_eq!(tree.expand_to(1), Some(1));
+        assert_eq!(tree.find_first(|item| item.contains('b')), Some(1));
+        assert_eq!(tree.find_first(|item| item == "c"), None);
+    }
+
+    #[test]
+    fn test_centered_offset() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<usize>::new();
+        for i in 0..20 {
+            tree.insert_item(i, Placement::After, i);
+        }
+
+        assert_eq!(tree.centered_offset(2, 10), 0);
+        assert_eq!(tree.centered_offset(10, 10), 5);
+        assert_eq!(tree.centered_offset(18, 10), 10);
+        assert_eq!(tree.centered_offset(5, 30), 0);
+    }
+
+    #[test]
+    fn test_move_item_focus() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+        tree.insert_item("4".to_string(), Placement::After, 2);
+
+        tree.set_selected_row(0);
+        assert_eq!(tree.move_item(0, Placement::LastChild, 1), None);
+        assert_eq!(tree.move_item(0, Placement::After, 3), Some(2));
+        assert_eq!(tree.row(), Some(2));
+        assert_eq!(
+            tree.iter()
+                .map(|(_, item)| item.as_str())
+                .collect::<Vec<_>>(),
+            vec!["3", "4", "1", "2"]
+        );
+    }
+
+    #[test]
+    fn test_item_handles() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        let root = tree
+            .insert_container_item_with_handle("root".to_string(), Placement::After, 0)
+            .unwrap();
+        let child = tree
+            .insert_item_with_handle("child".to_string(), Placement::LastChild, 0)
+            .unwrap();
+        assert_eq!(tree.row_for_handle(&root), Some(0));
+        assert_eq!(tree.row_for_handle(&child), None);
+
+        tree.expand_item(0);
+        tree.insert_item("first".to_string(), Placement::Before, 0);
+        assert_eq!(tree.row_for_handle(&root), Some(1));
+        assert_eq!(tree.row_for_handle(&child), Some(2));
+        assert_eq!(tree.handle(2), Some(child));
+
+        tree.borrow_item_by_handle_mut(&child).unwrap().push('!');
+        assert_eq!(tree.borrow_item(2), Some(&"child!".to_string()));
+
+        tree.collapse_item(1);
+        assert_eq!(
+            tree.remove_by_handle(&child),
+            Some(vec!["child!".to_string()])
+        );
+        assert_eq!(tree.borrow_item_by_handle(&child), None);
+        assert_eq!(tree.remove_by_handle(&child), None);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_symbols() {
+        use super::{Placement, TreeView, Vec2};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        assert_eq!(tree.item_width(0), Some(3));
+        assert_eq!(tree.required_size((10, 10).into()), Vec2::new(3, 1));
+
+        tree.set_symbols("[+]", "[-]", "*");
+        assert_eq!(tree.item_width(0), Some(5));
+        assert_eq!(tree.required_size((10, 10).into()), Vec2::new(5, 1));
+
+        let tree = TreeView::<String>::ascii();
+        assert_eq!(tree.collapsed_symbol, "+");
+        assert_eq!(tree.expanded_symbol, "-");
+        assert_eq!(tree.leaf_symbol, "*");
+        assert_eq!(tree.symbol_width, 2);
+    }
+
+    #[test]
+    fn test_set_item() {
+        use super::{Placement, TreeView, Vec2};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        assert_eq!(tree.required_size((20, 10).into()), Vec2::new(3, 1));
+
+        assert_eq!(
+            tree.set_item(0, "longer".to_string()),
+            Some("1".to_string())
+        );
+        assert_eq!(tree.borrow_item(0), Some(&"longer".to_string()));
+        assert_eq!(tree.required_size((20, 10).into()), Vec2::new(8, 1));
+
+        assert_eq!(tree.set_item(1, "2".to_string()), None);
+
+        // Wrapped row offsets are updated without another layout pass
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        tree.set_wrap(true);
+        tree.layout(Vec2::new(8, 10));
+        assert_eq!(tree.row_offset(1), 1);
+        tree.set_item(0, "one two three".to_string());
+        assert_eq!(tree.row_offset(1), 3);
+    }
+
+    #[test]
+    fn test_search() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new().search_enabled(true);
+        tree.insert_item("Cargo.toml".to_string(), Placement::After, 0);
+        tree.insert_item("src".to_string(), Placement::After, 0);
+        tree.insert_item("lib.rs".to_string(), Placement::LastChild, 1);
+        tree.insert_item("Cargo.lock".to_string(), Placement::After, 1);
+        tree.insert_item("README.md".to_string(), Placement::After, 3);
+
+        assert!(tree.on_event(Event::Char('c')).is_consumed());
+        assert_eq!(tree.row(), Some(3));
+
+        tree.on_event(Event::Char('a'));
+        tree.on_event(Event::Char('r'));
+        assert_eq!(tree.row(), Some(3));
+
+        tree.on_event(Event::Char('x'));
+        assert_eq!(tree.row(), Some(3));
+
+        // Navigation keys start a new search
+        tree.on_event(Event::Key(Key::Up));
+        tree.on_event(Event::Char('c'));
+        assert_eq!(tree.row(), Some(3));
+        tree.on_event(Event::Key(Key::Home));
+        tree.on_event(Event::Char('c'));
+        assert_eq!(tree.row(), Some(3));
+        tree.on_event(Event::Key(Key::Left));
+        tree.on_event(Event::Char('c'));
+        assert_eq!(tree.row(), Some(0));
+
+        tree.collapse_item(1);
+        tree.on_event(Event::Key(Key::Left));
+        tree.on_event(Event::Char('l'));
+        assert_eq!(tree.row(), Some(0));
+    }
+
+    #[test]
+    fn test_insert_item_sorted() {
+        use super::{Placement, TreeView};
+        use std::cmp::Ordering;
+
+        // Directories first, then files, both in alphabetical order
+        fn cmp(a: &String, b: &String) -> Ordering {
+            b.ends_with('/').cmp(&a.ends_with('/')).then(a.cmp(b))
+        }
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("root/".to_string(), Placement::After, 0);
+        tree.insert_item_sorted("b.rs".to_string(), 0, cmp);
+        tree.insert_container_item_sorted("src/".to_string(), 0, cmp);
+        tree.insert_item_sorted("a.rs".to_string(), 0, cmp);
+        tree.insert_container_item_sorted("examples/".to_string(), 0, cmp);
+
+        // Grandchildren are not compared against
+        tree.expand_item(2);
+        tree.insert_item("z.rs".to_string(), Placement::LastChild, 2);
+        tree.insert_item_sorted("c.rs".to_string(), 0, cmp);
+
+        tree.collapse_item(0);
+        assert_eq!(tree.insert_item_sorted("0.rs".to_string(), 0, cmp), None);
+        tree.expand_item(0);
+
+        let items: Vec<_> = tree.iter().map(|(_, item)| item.as_str()).collect();
+        assert_eq!(
+            items,
+            vec![
+                "root/",
+                "examples/",
+                "src/",
+                "z.rs",
+                "0.rs",
+                "a.rs",
+                "b.rs",
+                "c.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_viewport() {
+        use super::{Placement, TreeView};
+        use cursive::view::Scrollable;
+
+        let mut tree = TreeView::<usize>::new();
+        for i in 0..20 {
+            tree.insert_item(i, Placement::After, i);
+        }
+        assert_eq!(tree.visible_height(), 20);
+        assert_eq!(tree.viewport(), (0, 0));
+
+        // Lines past the last row are not part of the viewport
+        draw(&mut tree, Vec2::new(10, 25));
+        assert_eq!(tree.viewport(), (0, 20));
+
+        let mut scroll = tree.scrollable();
+        scroll.set_offset((0, 5));
+        draw(&mut scroll, Vec2::new(10, 4));
+        assert_eq!(scroll.get_inner().viewport(), (5, 9));
+
+        scroll.scroll_to_bottom();
+        draw(&mut scroll, Vec2::new(10, 4));
+        assert_eq!(scroll.get_inner().viewport(), (16, 20));
+    }
+
+    #[test]
+    fn test_counts() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        assert_eq!(tree.max_depth(), 0);
+        assert_eq!(tree.children_count(0), None);
+        assert_eq!(tree.descendant_count(0), None);
+
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::LastChild, 0);
+        tree.insert_item("4".to_string(), Placement::LastChild, 1);
+        tree.insert_item("5".to_string(), Placement::After, 0);
+        assert_eq!(tree.max_depth(), 3);
+        assert_eq!(tree.children_count(0), Some(2));
+        assert_eq!(tree.descendant_count(0), Some(3));
+        assert_eq!(tree.children_count(4), Some(0));
+
+        tree.collapse_item(0);
+        assert_eq!(tree.children_count(0), Some(2));
+        assert_eq!(tree.descendant_count(0), Some(3));
+        assert_eq!(tree.descendant_count(1), Some(0));
+        assert_eq!(tree.descendant_count(2), None);
+
+        assert!(!tree.is_leaf(0));
+        assert!(tree.is_leaf(1));
+        assert!(!tree.is_leaf(2));
+
+        tree.insert_container_item("6".to_string(), Placement::After, 1);
+        assert!(!tree.is_leaf(2));
+    }
+
+    #[test]
+    fn test_disabled_items() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 1);
+        tree.insert_item("4".to_string(), Placement::After, 0);
+        tree.set_item_enabled(0, false);
+        tree.set_item_enabled(1, false);
+        tree.set_item_enabled(3, false);
+        assert!(!tree.is_item_enabled(0));
+        assert!(tree.is_item_enabled(2));
+        assert!(!tree.is_item_enabled(4));
+
+        tree.set_selected_row(2);
+        tree.on_event(Event::Key(Key::Up));
+        assert_eq!(tree.row(), Some(2));
+        tree.on_event(Event::Key(Key::Down));
+        assert_eq!(tree.row(), Some(2));
+        tree.on_event(Event::Key(Key::Home));
+        assert_eq!(tree.row(), Some(2));
+        tree.on_event(Event::Key(Key::End));
+        assert_eq!(tree.row(), Some(2));
+
+        tree.set_item_enabled(3, true);
+        tree.on_event(Event::Key(Key::PageDown));
+        assert_eq!(tree.row(), Some(3));
+        tree.on_event(Event::Key(Key::PageUp));
+        assert_eq!(tree.row(), Some(2));
+
+        // Disabled containers can not be collapsed by the user
+        tree.set_selected_row(0);
+        assert!(!tree.on_event(Event::Key(Key::Enter)).is_consumed());
+        assert_eq!(tree.is_collapsed(0), Some(false));
+    }
+
+    #[test]
+    fn test_move_item_up_and_down() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("1a".to_string(), Placement::LastChild, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        tree.insert_item("3".to_string(), Placement::After, 2);
+        tree.collapse_item(0);
+
+        tree.set_selected_row(0);
+        assert_eq!(tree.move_item_down(0), Some(1));
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.move_item_down(1), Some(2));
+        assert_eq!(tree.move_item_down(2), Some(2));
+        assert_eq!(tree.row(), Some(2));
+
+        assert_eq!(tree.move_item_up(0), Some(0));
+        assert_eq!(tree.move_item_up(1), Some(0));
+        assert_eq!(tree.row(), Some(2));
+        assert_eq!(tree.move_item_up(3), None);
+
+        let items: Vec<_> = tree.iter().map(|(_, item)| item.as_str()).collect();
+        assert_eq!(items, vec!["3", "2", "1"]);
+    }
+
+    #[test]
+    fn test_on_leave() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let valid = Arc::new(AtomicBool::new(false));
+        let is_valid = valid.clone();
+        let mut tree = TreeView::<String>::new()
+            .on_leave(move |row| row != 0 || is_valid.load(Ordering::SeqCst));
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+
+        assert!(tree.on_event(Event::Key(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(0));
+
+        valid.store(true, Ordering::SeqCst);
+        tree.on_event(Event::Key(Key::Down));
+        assert_eq!(tree.row(), Some(1));
+
+        valid.store(false, Ordering::SeqCst);
+        tree.on_event(Event::Key(Key::Up));
+        assert_eq!(tree.row(), Some(0));
+    }
+
+    #[test]
+    fn test_promote_and_demote() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        tree.insert_item("2a".to_string(), Placement::LastChild, 1);
+        tree.insert_item("3".to_string(), Placement::After, 1);
+        tree.insert_item("3a".to_string(), Placement::LastChild, 3);
+
+        assert_eq!(tree.demote_item(0), None);
+        assert_eq!(tree.promote_item(0), None);
+
+        tree.set_selected_row(1);
+        assert_eq!(tree.demote_item(1), Some(1));
+        assert_eq!(tree.item_level(1), Some(1));
+        assert_eq!(tree.item_level(2), Some(2));
+        assert_eq!(tree.row(), Some(1));
+
+        // Demoting into a collapsed sibling hides the item
+        tree.collapse_item(0);
+        tree.set_selected_row(1);
+        assert_eq!(tree.demote_item(1), None);
+        assert_eq!(tree.row(), Some(0));
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.visible_height(), 1);
+
+        tree.expand_all();
+        assert_eq!(tree.item_level(3), Some(1));
+        assert_eq!(tree.item_level(4), Some(2));
+
+        tree.set_selected_row(3);
+        assert_eq!(tree.promote_item(3), Some(3));
+        assert_eq!(tree.promote_item(1), Some(1));
+        assert_eq!(tree.row(), Some(3));
+
+        let items: Vec<_> = tree
+            .iter()
+            .map(|(row, item)| (tree.item_level(row).unwrap(), item.as_str()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![(0, "1"), (0, "2"), (1, "2a"), (0, "3"), (1, "3a")]
+        );
+    }
+
+    #[test]
+    fn test_retain() {
+        use super::{Placement, RetainPolicy, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::After, 0);
+        tree.insert_item("xc".to_string(), Placement::LastChild, 1);
+        tree.insert_item("d".to_string(), Placement::LastChild, 2);
+        tree.insert_item("xe".to_string(), Placement::After, 1);
+        tree.collapse_item(1);
+
+        tree.set_selected_row(2);
+        tree.retain(|item| !item.starts_with('x'), RetainPolicy::ExtractChildren);
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.visible_height(), 2);
+
+        tree.expand_item(1);
+        tree.set_selected_row(2);
+        tree.retain(|item| item != "a", RetainPolicy::RemoveChildren);
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.borrow_item(1), Some(&"d".to_string()));
+    }
+
+    #[test]
+    fn test_item_path() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::After, 0);
+        tree.insert_item("c".to_string(), Placement::LastChild, 1);
+        tree.insert_item("d".to_string(), Placement::LastChild, 2);
+        assert_eq!(tree.item_path(0), Some(vec![0]));
+        assert_eq!(tree.item_path(3), Some(vec![1, 2, 3]));
+        assert_eq!(tree.path_string(3, "/"), Some("b/c/d".to_string()));
+
+        tree.collapse_item(1);
+        assert_eq!(tree.item_path(1), Some(vec![1]));
+        assert_eq!(tree.item_path(2), None);
+        assert_eq!(tree.path_string(2, "/"), None);
+    }
+
+    #[test]
+    fn test_sort_subtree_by() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<usize>::new();
+        tree.insert_item(1, Placement::After, 0);
+        tree.insert_item(13, Placement::LastChild, 0);
+        tree.insert_item(132, Placement::LastChild, 1);
+        tree.insert_item(131, Placement::LastChild, 1);
+        tree.insert_item(12, Placement::LastChild, 0);
+        tree.insert_item(11, Placement::LastChild, 0);
+        tree.insert_item(112, Placement::LastChild, 5);
+        tree.insert_item(111, Placement::LastChild, 5);
+        tree.collapse_item(5);
+
+        tree.set_selected_row(3);
+        tree.sort_subtree_by(0, |a, b| a.cmp(b));
+        assert_eq!(tree.row(), Some(4));
+
+        let values: Vec<usize> = tree.iter().map(|(_, item)| *item).collect();
+        assert_eq!(values, vec![1, 11, 12, 13, 131, 132]);
+
+        tree.expand_item(1);
+        let values: Vec<usize> = tree.iter().map(|(_, item)| *item).collect();
+        assert_eq!(values, vec![1, 11, 111, 112, 12, 13, 131, 132]);
+
+        tree.set_selected_row(7);
+        tree.sort_children_by(0, |a, b| b.cmp(a));
+        assert_eq!(tree.row(), Some(3));
+        let values: Vec<usize> = tree.iter().map(|(_, item)| *item).collect();
+        assert_eq!(values, vec![1, 13, 131, 132, 12, 11, 111, 112]);
+    }
+
+    #[test]
+    fn test_paths() {
+        use super::TreeView;
+
+        let paths: Vec<&[usize]> = vec![
+            &[0],
+            &[1],
+            &[0, 0],
+            &[0, 1],
+            &[0, 1, 0],
+            &[0, 1, 0, 0],
+            &[0, 0, 0],
+            &[1, 0],
+            &[0, 2],
+        ];
+
+        let mut tree = TreeView::<String>::new();
+        for path in &paths {
+            let name = format!("{:?}", path);
+            assert!(tree.insert_at_path(path, name).is_some());
+            tree.collapse_all();
+        }
+        assert!(tree.insert_at_path(&[5, 0], "none".to_string()).is_none());
+        assert!(tree.insert_at_path(&[], "none".to_string()).is_none());
+
+        for path in &paths {
+            assert_eq!(tree.borrow_item_at_path(path), Some(&format!("{:?}", path)));
+        }
+        assert_eq!(tree.visible_height(), 2);
+        assert_eq!(tree.borrow_item_at_path(&[0, 3]), None);
+
+        assert_eq!(
+            tree.remove_at_path(&[0, 1]),
+            Some(vec![
+                "[0, 1]".to_string(),
+                "[0, 1, 0]".to_string(),
+                "[0, 1, 0, 0]".to_string()
+            ])
+        );
+        assert_eq!(
+            tree.borrow_item_at_path(&[0, 1]),
+            Some(&"[0, 2]".to_string())
+        );
+        assert_eq!(tree.remove_at_path(&[0, 2]), None);
+        assert_eq!(tree.len(), 6);
+    }
+
+    #[test]
+    fn test_level_vec() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_container_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::LastChild, 1);
+        tree.insert_item("4".to_string(), Placement::LastChild, 0);
+        tree.insert_container_item("5".to_string(), Placement::After, 0);
+        tree.insert_item("6".to_string(), Placement::After, 0);
+
+        let levels: Vec<(usize, bool, String)> = tree
+            .to_level_vec()
+            .into_iter()
+            .map(|(level, is_container, item)| (level, is_container, item.clone()))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                (0, true, "1".to_string()),
+                (1, true, "2".to_string()),
+                (2, false, "3".to_string()),
+                (1, false, "4".to_string()),
+                (0, false, "6".to_string()),
+                (0, true, "5".to_string())
+            ]
+        );
+
+        let copy = TreeView::from_level_vec(levels);
+        assert_eq!(copy.to_level_vec(), tree.to_level_vec());
+    }
+
+    #[test]
+    fn test_item_children() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        assert!(tree.item_children(0).is_empty());
+
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::LastChild, 1);
+        tree.insert_item("4".to_string(), Placement::LastChild, 2);
+        tree.insert_item("5".to_string(), Placement::After, 2);
+        tree.insert_item("6".to_string(), Placement::LastChild, 0);
+        assert_eq!(tree.item_children(0), vec![1, 5]);
+        assert_eq!(tree.item_children(1), vec![2, 4]);
+        assert!(tree.item_children(3).is_empty());
+        assert!(tree.item_children(6).is_empty());
+
+        tree.collapse_item(2);
+        assert_eq!(tree.item_children(1), vec![2, 3]);
+        assert_eq!(tree.item_children(0), vec![1, 4]);
+
+        tree.collapse_item(0);
+        assert!(tree.item_children(0).is_empty());
+    }
+
+    #[test]
+    fn test_deselect() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new().allow_deselect(true);
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        tree.set_selected_row(1);
+        assert_eq!(tree.row(), Some(1));
+
+        assert!(tree.on_event(Event::Key(Key::Esc)).is_consumed());
+        assert_eq!(tree.row(), None);
+        assert!(!tree.on_event(Event::Key(Key::Esc)).is_consumed());
+        assert!(!tree.on_event(Event::Key(Key::Enter)).is_consumed());
+
+        assert!(tree.on_event(Event::Key(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(0));
+
+        tree.deselect();
+        tree.set_allow_deselect(false);
+        assert_eq!(tree.row(), None);
+        tree.set_selected_row(1);
+        assert!(!tree.on_event(Event::Key(Key::Esc)).is_consumed());
+        assert_eq!(tree.row(), Some(1));
+    }
+
+    #[test]
+    fn test_item_callbacks() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (submit, select, collapse, row) = (log.clone(), log.clone(), log.clone(), log.clone());
+        let mut tree = TreeView::<String>::new()
+            .on_submit(move |_, r| row.lock().unwrap().push(format!("row {}", r)))
+            .on_submit_item(move |_, item| submit.lock().unwrap().push(format!("submit {}", item)))
+            .on_select_item(move |_, item| select.lock().unwrap().push(format!("select {}", item)))
+            .on_collapse_item(move |_, item, is_collapsed, children| {
+                collapse
+                    .lock()
+                    .unwrap()
+                    .push(format!("collapse {} {} {}", item, is_collapsed, children))
+            });
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        tree.on_event(Event::Key(Key::Down)).process(&mut siv);
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "collapse 1 true 1".to_string(),
+                "select 3".to_string(),
+                "row 1".to_string(),
+                "submit 3".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vim_keys() {
+        use super::{Placement, TreeView};
+        use cursive::event::Event;
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new().search_enabled(true);
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.expand_item(0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+        tree.insert_item("la".to_string(), Placement::After, 2);
+
+        tree.on_event(Event::Char('l'));
+        assert_eq!(tree.row(), Some(3));
+
+        tree.set_vim_keys(true);
+        tree.set_selected_row(0);
+        tree.on_event(Event::Char('h'));
+        assert_eq!(tree.is_collapsed(0), Some(true));
+        assert!(!tree.on_event(Event::Char('h')).is_consumed());
+        assert_eq!(tree.row(), Some(0));
+
+        tree.on_event(Event::Char('l'));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        assert_eq!(tree.row(), Some(0));
+
+        assert!(tree.on_event(Event::Char('l')).is_consumed());
+        assert_eq!(tree.row(), Some(1));
+        assert!(!tree.on_event(Event::Char('l')).is_consumed());
+
+        assert!(tree.on_event(Event::Char('h')).is_consumed());
+        assert_eq!(tree.row(), Some(0));
+    }
+
+    #[test]
+    fn test_toggle_focused() {
+        use super::{Placement, TreeView};
+        use std::sync::{Arc, Mutex};
+
+        let toggled = Arc::new(Mutex::new(Vec::new()));
+        let log = toggled.clone();
+        let mut tree = TreeView::<String>::new().on_collapse(move |_, row, is_collapsed, _| {
+            log.lock().unwrap().push((row, is_collapsed))
+        });
+        assert!(tree.toggle_focused().is_none());
+
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+
+        let mut siv = cursive::Cursive::new();
+        tree.toggle_focused().unwrap().process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        tree.toggle_focused().unwrap().process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(true));
+        assert_eq!(*toggled.lock().unwrap(), vec![(0, false), (0, true)]);
+
+        tree.set_selected_row(1);
+        assert!(tree.toggle_focused().is_none());
+    }
+
+    #[test]
+    fn test_take_selection_changed() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new();
+        assert!(!tree.take_selection_changed());
+
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+        assert!(tree.take_selection_changed());
+        assert!(!tree.take_selection_changed());
+
+        tree.on_event(Event::Key(Key::Down));
+        assert!(!tree.take_selection_changed());
+
+        tree.collapse_item(0);
+        assert!(tree.take_selection_changed());
+
+        tree.set_selected_row(1);
+        assert!(!tree.take_selection_changed());
+        tree.insert_item("0".to_string(), Placement::Before, 0);
+        assert!(!tree.take_selection_changed());
+        assert_eq!(tree.row(), Some(2));
+        tree.remove_item(2);
+        assert!(tree.take_selection_changed());
+
+        tree.set_notify_on_programmatic_select(true);
+        tree.set_selected_row(0);
+        assert!(tree.take_selection_changed());
+    }
+
+    #[test]
+    fn test_expand_callbacks() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (expand, collapse) = (log.clone(), log.clone());
+        let mut tree = TreeView::<String>::new()
+            .on_expand(move |_, row, children| expand.lock().unwrap().push((true, row, children)))
+            .on_collapse_only(move |_, row, children| {
+                collapse.lock().unwrap().push((false, row, children))
+            })
+            .on_before_expand(|tree, row| {
+                if tree.children_count(row) == Some(0) {
+                    tree.insert_item("Loaded".to_string(), Placement::LastChild, row);
+                }
+                row == 0
+            });
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.insert_container_item("2".to_string(), Placement::After, 0);
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        assert_eq!(tree.borrow_item(1), Some(&"Loaded".to_string()));
+
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(true));
+
+        tree.set_selected_row(1);
+        assert!(tree.on_event(Event::Key(Key::Enter)).is_consumed());
+        assert_eq!(tree.is_collapsed(1), Some(true));
+        assert_eq!(tree.children_count(1), Some(1));
+
+        assert_eq!(*log.lock().unwrap(), vec![(true, 0, 1), (false, 0, 1)]);
+    }
+
+    #[test]
+    fn test_set_selected_row_notify() {
+        use super::{Placement, TreeView};
+        use std::sync::{Arc, Mutex};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        assert!(tree.set_selected_row_notify(1).is_none());
+
+        let selected = Arc::new(Mutex::new(None));
+        let log = selected.clone();
+        tree.set_on_select(move |_, row| *log.lock().unwrap() = Some(row));
+        assert!(tree.set_selected_row_notify(5).is_none());
+
+        let cb = tree.set_selected_row_notify(1);
+        assert!(cb.is_some());
+        assert_eq!(tree.row(), Some(1));
+        assert!(!tree.take_selection_changed());
+
+        cb.unwrap()(&mut cursive::Cursive::new());
+        assert_eq!(*selected.lock().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_wrap() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key, MouseButton, MouseEvent};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new().wrap(true);
+        tree.insert_item("one two three".to_string(), Placement::After, 0);
+        tree.insert_item("four".to_string(), Placement::After, 0);
+
+        assert_eq!(tree.required_size(Vec2::new(8, 10)), Vec2::new(8, 4));
+        tree.layout(Vec2::new(8, 4));
+
+        tree.on_event(Event::Key(Key::Down));
+        assert_eq!(tree.row(), Some(1));
+
+        tree.on_event(Event::Mouse {
+            position: Vec2::new(3, 2),
+            offset: Vec2::zero(),
+            event: MouseEvent::Press(MouseButton::Left),
+        });
+        assert_eq!(tree.row(), Some(0));
+
+        tree.set_wrap(false);
+        assert_eq!(tree.required_size(Vec2::new(8, 10)), Vec2::new(15, 2));
+    }
+
+    #[test]
+    fn test_checkboxes() {
+        use super::{CheckState, Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new().multi_select(true);
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::LastChild, 0);
+        assert_eq!(tree.item_width(0), Some(3));
+
+        tree.set_checkboxes(true);
+        assert_eq!(tree.item_width(0), Some(7));
+        assert_eq!(tree.required_size(Vec2::new(20, 20)), Vec2::new(9, 3));
+
+        tree.on_event(Event::Char(' '));
+        assert_eq!(tree.checked_rows(), vec![0, 1, 2]);
+        assert!(tree.selected_rows().is_empty());
+
+        tree.on_event(Event::Key(Key::Down));
+        tree.on_event(Event::Char(' '));
+        assert_eq!(tree.check_state(0), Some(CheckState::Partial));
+        assert_eq!(tree.checked_rows(), vec![2]);
+
+        tree.on_event(Event::Key(Key::Up));
+        tree.on_event(Event::Char(' '));
+        assert_eq!(tree.checked_rows(), vec![0, 1, 2]);
+        tree.on_event(Event::Char(' '));
+        assert!(tree.checked_rows().is_empty());
+    }
+
+    #[test]
+    fn test_on_check() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let checks = Arc::new(Mutex::new(Vec::new()));
+        let log = checks.clone();
+        let mut tree = TreeView::<String>::new()
+            .checkboxes(true)
+            .on_check(move |_, row, checked| log.lock().unwrap().push((row, checked)));
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Key(Key::Down)).process(&mut siv);
+        tree.on_event(Event::Char(' ')).process(&mut siv);
+        tree.on_event(Event::Char(' ')).process(&mut siv);
+        tree.on_event(Event::Key(Key::Up)).process(&mut siv);
+        tree.on_event(Event::Char(' ')).process(&mut siv);
+        assert_eq!(
+            *checks.lock().unwrap(),
+            vec![(1, true), (1, false), (0, true)]
+        );
+
+        tree.collapse_item(0);
+        assert_eq!(tree.checked_rows(), vec![0]);
+        assert_eq!(tree.checked_indices(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_row_index_conversion() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+
+        assert_eq!(tree.row_to_index(2), Some(2));
+        assert_eq!(tree.index_to_row(2), Some(2));
+
+        tree.collapse_item(0);
+        assert_eq!(tree.row_to_index(1), Some(2));
+        assert_eq!(tree.row_to_index(2), None);
+        assert_eq!(tree.index_to_row(2), Some(1));
+        assert_eq!(tree.index_to_row(1), None);
+        assert_eq!(tree.index_to_row(3), None);
+    }
+
+    #[test]
+    fn test_truncate() {
+        use super::{truncate, Truncate};
+
+        assert_eq!(truncate("café au lait", 5, Truncate::End), "café…");
+        assert_eq!(truncate("café au lait", 5, Truncate::None), "café au lait");
+        assert_eq!(truncate("café", 4, Truncate::End), "café");
+        assert_eq!(truncate("café", 0, Truncate::End), "");
+
+        assert_eq!(truncate("日本語テキスト", 7, Truncate::End), "日本語…");
+        assert_eq!(truncate("日本語テキスト", 6, Truncate::End), "日本…");
+        assert_eq!(truncate("日本語テキスト", 6, Truncate::Middle), "日…ト");
+
+        assert_eq!(
+            truncate("/home/user/deep/file.rs", 10, Truncate::Middle),
+            "/home…e.rs"
+        );
+    }
+
+    #[test]
+    fn test_lazy_loader() {
+        use super::{LazyTree, Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        struct Loader(usize);
+
+        impl LazyTree<String> for Loader {
+            fn load_children(&mut self, item: &String) -> Vec<(String, bool)> {
+                self.0 += 1;
+                vec![
+                    (format!("{}.1", item), true),
+                    (format!("{}.2", item), false),
+                ]
+            }
+        }
+
+        let mut tree = TreeView::<String>::new().lazy_loader(Loader(0));
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        assert_eq!(tree.children_count(0), Some(2));
+        assert_eq!(tree.borrow_item(1), Some(&"1.1".to_string()));
+        assert_eq!(tree.is_collapsed(1), Some(true));
+        assert_eq!(tree.is_collapsed(2), Some(false));
+
+        tree.on_event(Event::Key(Key::Enter));
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(tree.children_count(0), Some(2));
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_display_width() {
+        use super::{Placement, TreeView};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("日本語".to_string(), Placement::After, 0);
+        tree.insert_item("café".to_string(), Placement::LastChild, 0);
+        tree.insert_item("cafe\u{301}".to_string(), Placement::LastChild, 0);
+
+        assert_eq!(tree.item_width(0), Some(8));
+        assert_eq!(tree.item_width(1), Some(6));
+        assert_eq!(tree.item_width(2), Some(6));
+        assert_eq!(tree.first_col(1), Some(2));
+        assert_eq!(tree.required_size(Vec2::new(20, 20)), Vec2::new(8, 3));
+
+        // Wrapped lines never exceed the available columns
+        let lines = tree.wrap_item(&tree.list.items()[0], 7);
+        assert_eq!(lines, vec!["日本", "語"]);
+    }
+
+    #[test]
+    fn test_subtree_levels() {
+        use super::{ExtractedTree, Placement, TreeView};
+        use std::collections::HashSet;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, true, "c".to_string()),
+            (1, false, "d".to_string()),
+            (0, false, "e".to_string()),
+        ]);
+        let original = tree
+            .to_level_vec()
+            .into_iter()
+            .map(|(level, is_container, item)| (level, is_container, item.clone()))
+            .collect::<Vec<_>>();
+        let height = tree.len();
+
+        let levels = tree.remove_subtree(0).unwrap().into_levels();
+        assert_eq!(levels[0], (0, true, "a".to_string()));
+        assert_eq!(levels[2], (2, true, "c".to_string()));
+        assert_eq!(tree.len(), 1);
+
+        let subtree = ExtractedTree::from_levels(levels);
+        assert_eq!(tree.insert_subtree(subtree, Placement::Before, 0), Some(0));
+        assert_eq!(tree.len(), height);
+        assert_eq!(
+            tree.to_level_vec(),
+            original
+                .iter()
+                .map(|(level, is_container, item)| (*level, *is_container, item))
+                .collect::<Vec<_>>()
+        );
+
+        let copy = ExtractedTree::from_levels(vec![(0, false, "f".to_string())]);
+        tree.insert_subtree(copy, Placement::After, 1);
+        tree.expand_all();
+        let handles: HashSet<_> = (0..tree.len()).filter_map(|row| tree.handle(row)).collect();
+        assert_eq!(handles.len(), tree.len());
+    }
+
+    #[test]
+    fn test_indent_guides() {
+        use super::TreeView;
+
+        let tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "c".to_string()),
+            (3, false, "d".to_string()),
+            (1, false, "e".to_string()),
+            (2, false, "f".to_string()),
+            (0, false, "g".to_string()),
+        ]);
+
+        let guides = tree.indent_guide_lines();
+        let first: Vec<&str> = guides.iter().map(|(first, _)| first.as_str()).collect();
+        assert_eq!(first, vec!["", "├─", "│ └─", "│   └─", "└─", "  └─", ""]);
+        assert_eq!(guides[1].1, "│ ");
+        assert_eq!(guides[2].1, "│   ");
+    }
+
+    #[test]
+    fn test_sibling_navigation() {
+        use super::TreeView;
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "c".to_string()),
+            (1, false, "d".to_string()),
+            (1, false, "e".to_string()),
+            (0, false, "f".to_string()),
+        ]);
+
+        assert!(tree.on_event(Event::Shift(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(5));
+        assert!(!tree.on_event(Event::Shift(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(5));
+
+        tree.set_selected_row(1);
+        tree.set_item_enabled(3, false);
+        assert!(tree.on_event(Event::Shift(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(4));
+        assert!(tree.on_event(Event::Shift(Key::Up)).is_consumed());
+        assert_eq!(tree.row(), Some(1));
+        assert!(!tree.on_event(Event::Shift(Key::Up)).is_consumed());
+
+        tree.set_selected_row(2);
+        assert!(!tree.on_event(Event::Shift(Key::Down)).is_consumed());
+        assert!(!tree.on_event(Event::Shift(Key::Up)).is_consumed());
+
+        // Collapsed siblings count as single rows
+        tree.set_selected_row(0);
+        tree.collapse_item(0);
+        assert!(tree.on_event(Event::Ctrl(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.select_next_sibling(), Some(1));
+        assert_eq!(tree.select_prev_sibling(), Some(0));
+        assert!(!tree.on_event(Event::Ctrl(Key::Up)).is_consumed());
+    }
+
+    #[test]
+    fn test_max_visible_depth() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "c".to_string()),
+            (3, false, "d".to_string()),
+            (1, false, "e".to_string()),
+            (0, false, "f".to_string()),
+        ]);
+        tree.set_selected_row(3);
+
+        tree.set_max_visible_depth(Some(2));
+        assert_eq!(tree.visible_height(), 4);
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.borrow_item(2), Some(&"e".to_string()));
+        assert_eq!(tree.row_to_index(2), Some(4));
+        assert_eq!(tree.index_to_row(5), Some(3));
+
+        tree.expand_item(1);
+        tree.expand_all();
+        assert_eq!(tree.visible_height(), 4);
+        assert!(!tree.on_event(Event::Key(Key::Enter)).is_consumed());
+        assert_eq!(tree.visible_height(), 4);
+        assert_eq!(tree.expand_to(3), None);
+
+        tree.insert_item("g".to_string(), Placement::LastChild, 2);
+        tree.layout(Vec2::new(10, 10));
+        assert_eq!(tree.visible_height(), 4);
+        assert_eq!(tree.required_size(Vec2::new(10, 10)).y, 4);
+
+        tree.set_max_visible_depth(None);
+        assert_eq!(tree.visible_height(), 7);
+    }
+
+    #[test]
+    fn test_item_renderer() {
+        use super::{Placement, TreeView};
+        use cursive::theme::Effect;
+        use cursive::utils::markup::StyledString;
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new().item_renderer(|item: &String| {
+            let mut styled = StyledString::plain(item.as_str());
+            styled.append_styled(" 日本", Effect::Bold);
+            styled
+        });
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("22".to_string(), Placement::LastChild, 0);
+
+        assert_eq!(tree.item_width(0), Some(8));
+        assert_eq!(tree.item_width(1), Some(9));
+        assert_eq!(tree.required_size(Vec2::new(20, 20)), Vec2::new(11, 2));
+    }
+
+    #[test]
+    fn test_filter() {
+        use super::{fuzzy_match, Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        assert!(fuzzy_match("tvw", "tree_view"));
+        assert!(fuzzy_match("", "tree_view"));
+        assert!(!fuzzy_match("wv", "tree_view"));
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, true, "src".to_string()),
+            (1, false, "lib.rs".to_string()),
+            (1, true, "tree_list".to_string()),
+            (2, false, "Mod.rs".to_string()),
+            (0, false, "Cargo.toml".to_string()),
+        ]);
+        tree.expand_item(0);
+        tree.set_selected_row(1);
+
+        tree.set_filter(Some("mod".to_string()));
+        assert_eq!(tree.visible_height(), 3);
+        assert_eq!(tree.borrow_item(2), Some(&"Mod.rs".to_string()));
+        assert_eq!(tree.row(), Some(0));
+        assert!(!tree.on_event(Event::Key(Key::Enter)).is_consumed());
+
+        tree.insert_item("model".to_string(), Placement::After, 2);
+        tree.insert_item("other".to_string(), Placement::After, 2);
+        assert_eq!(tree.visible_height(), 5);
+        tree.layout(Vec2::new(20, 20));
+        assert_eq!(tree.visible_height(), 4);
+
+        tree.set_selected_row(2);
+        tree.set_filter(Some("toml".to_string()));
+        assert_eq!(tree.visible_height(), 1);
+        assert_eq!(tree.row(), Some(0));
+
+        tree.set_filter(None);
+        assert_eq!(tree.visible_height(), 4);
+        assert_eq!(tree.row(), Some(3));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        assert_eq!(tree.is_collapsed(2), Some(true));
+    }
+
+    #[test]
+    fn test_update_item() {
+        use super::{Placement, TreeView};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new().wrap(true);
+        tree.insert_item("abc".to_string(), Placement::After, 0);
+        tree.insert_item("def".to_string(), Placement::After, 0);
+        tree.layout(Vec2::new(6, 10));
+        assert_eq!(tree.row_y(1), 1);
+
+        assert!(tree.update_item(0, |value| value.push_str(" abc abc")));
+        assert_eq!(tree.borrow_item(0), Some(&"abc abc abc".to_string()));
+        assert_eq!(tree.row_y(1), 3);
+
+        assert!(!tree.update_item(2, |value| value.clear()));
+        assert_eq!(tree.required_size(Vec2::new(100, 10)), Vec2::new(13, 2));
+    }
+
+    #[test]
+    fn test_max_item_width() {
+        use super::{Placement, TreeView};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new();
+        assert_eq!(tree.required_size(Vec2::new(80, 24)), Vec2::new(0, 0));
+
+        tree.insert_item("abc".to_string(), Placement::After, 0);
+        tree.insert_item("defgh".to_string(), Placement::LastChild, 0);
+        assert_eq!(tree.required_size(Vec2::new(80, 24)), Vec2::new(9, 2));
+        assert_eq!(tree.max_width, Some((7, tree.list.revision())));
+
+        tree.borrow_item_mut(0).unwrap().push_str("abcdefgh");
+        assert_eq!(tree.required_size(Vec2::new(80, 24)), Vec2::new(13, 2));
+
+        tree.clear();
+        assert_eq!(tree.required_size(Vec2::new(80, 24)), Vec2::new(0, 0));
+
+        tree.insert_item("ab".to_string(), Placement::After, 0);
+        tree.set_item_renderer(|value: &String| format!("{}{}", value, value).into());
+        assert_eq!(tree.required_size(Vec2::new(80, 24)), Vec2::new(6, 1));
+    }
+
+    #[test]
+    fn test_ancestors() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (1, false, "c".to_string()),
+            (2, false, "d".to_string()),
+            (0, false, "e".to_string()),
+        ]);
+
+        let ancestors: Vec<(usize, &String)> = tree.ancestors(3).collect();
+        assert_eq!(
+            ancestors,
+            vec![(2, &"c".to_string()), (0, &"a".to_string())]
+        );
+        assert_eq!(tree.ancestors(0).count(), 0);
+        assert_eq!(tree.ancestors(5).count(), 0);
+
+        tree.collapse_item(2);
+        assert_eq!(tree.ancestors(3).count(), 0);
+    }
+
+    #[test]
+    fn test_descendants() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (1, false, "c".to_string()),
+            (2, false, "d".to_string()),
+            (0, false, "e".to_string()),
+        ]);
+        tree.collapse_item(2);
+
+        let descendants: Vec<(usize, &str)> = tree
+            .descendants(0)
+            .map(|(index, value)| (index, value.as_str()))
+            .collect();
+        assert_eq!(descendants, vec![(1, "b"), (2, "c"), (3, "d")]);
+        assert_eq!(tree.index_to_row(3), None);
+        assert_eq!(tree.descendants(3).count(), 0);
+        assert_eq!(tree.descendants(4).count(), 0);
+    }
+
+    #[test]
+    fn test_arrow_keys() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let toggled = Arc::new(Mutex::new(Vec::new()));
+        let log = toggled.clone();
+        let mut tree = TreeView::<String>::new().on_collapse(move |_, row, is_collapsed, _| {
+            log.lock().unwrap().push((row, is_collapsed))
+        });
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::LastChild, 0);
+        tree.insert_item("3".to_string(), Placement::After, 0);
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Key(Key::Right)).process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        tree.on_event(Event::Key(Key::Right)).process(&mut siv);
+        assert_eq!(tree.row(), Some(1));
+        assert!(!tree.on_event(Event::Key(Key::Right)).is_consumed());
+
+        tree.on_event(Event::Key(Key::Left)).process(&mut siv);
+        assert_eq!(tree.row(), Some(0));
+        tree.on_event(Event::Key(Key::Left)).process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(true));
+        assert!(!tree.on_event(Event::Key(Key::Left)).is_consumed());
+        assert_eq!(*toggled.lock().unwrap(), vec![(0, false), (0, true)]);
+
+        tree.set_arrow_keys(false);
+        assert!(!tree.on_event(Event::Key(Key::Right)).is_consumed());
+        assert_eq!(tree.is_collapsed(0), Some(true));
+    }
+
+    #[test]
+    fn test_typeahead() {
+        use super::{TreeView, TypeaheadMode};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, true, "src".to_string()),
+            (1, false, "lib.rs".to_string()),
+            (1, false, "main.rs".to_string()),
+            (0, false, "Cargo.toml".to_string()),
+        ])
+        .typeahead(TypeaheadMode::Substring);
+
+        tree.on_event(Event::Char('r'));
+        tree.on_event(Event::Char('g'));
+        assert_eq!(tree.search_query(), "rg");
+        assert_eq!(tree.row(), Some(1));
+
+        tree.on_event(Event::Key(Key::Esc));
+        assert_eq!(tree.search_query(), "");
+
+        tree.set_selected_row(0);
+        tree.on_event(Event::Char('m'));
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.is_collapsed(0), Some(true));
+
+        tree.on_event(Event::Key(Key::Esc));
+        tree.set_typeahead_expand(true);
+        tree.set_selected_row(0);
+        tree.on_event(Event::Char('m'));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        assert_eq!(tree.row(), Some(2));
+
+        tree.set_typeahead(TypeaheadMode::Off);
+        assert!(!tree.on_event(Event::Char('l')).is_consumed());
+    }
+
+    #[test]
+    fn test_toggle_descendants() {
+        use super::TreeView;
+        use cursive::event::Event;
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let toggled = Arc::new(Mutex::new(Vec::new()));
+        let log = toggled.clone();
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, true, "a".to_string()),
+            (1, true, "b".to_string()),
+            (2, true, "c".to_string()),
+            (3, false, "d".to_string()),
+            (1, false, "e".to_string()),
+            (0, false, "f".to_string()),
+        ])
+        .on_collapse(move |_, row, is_collapsed, _| log.lock().unwrap().push((row, is_collapsed)));
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Char('*')).process(&mut siv);
+        assert_eq!(tree.visible_height(), 6);
+
+        // Partially expanded subtrees are expanded first
+        tree.collapse_item(2);
+        assert_eq!(tree.visible_height(), 5);
+        tree.on_event(Event::Char('*')).process(&mut siv);
+        assert_eq!(tree.visible_height(), 6);
+
+        tree.on_event(Event::Char('*')).process(&mut siv);
+        assert_eq!(tree.visible_height(), 2);
+        assert_eq!(
+            *toggled.lock().unwrap(),
+            vec![(0, false), (0, false), (0, true)]
+        );
+
+        // Heights of nested items stay consistent
+        tree.expand_item(0);
+        assert_eq!(tree.visible_height(), 4);
+        tree.expand_item(1);
+        tree.expand_item(2);
+        assert_eq!(tree.visible_height(), 6);
+
+        tree.set_max_visible_depth(Some(2));
+        tree.expand_descendants(0);
+        assert_eq!(tree.visible_height(), 4);
+
+        tree.on_event(Event::Char('*')).process(&mut siv);
+        assert_eq!(tree.visible_height(), 2);
+
+        tree.expand_descendants(0);
+        tree.set_selected_row(3);
+        assert!(!tree.on_event(Event::Char('*')).is_consumed());
+    }
+
+    #[test]
+    fn test_page_size() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::Scrollable;
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new();
+        for i in 0..30 {
+            tree.insert_item(i.to_string(), Placement::After, i);
+        }
+
+        tree.layout(Vec2::new(10, 8));
+        tree.set_selected_row(2);
+        tree.on_event(Event::Key(Key::PageDown));
+        assert_eq!(tree.row(), Some(9));
+        tree.on_event(Event::Key(Key::PageUp));
+        assert_eq!(tree.row(), Some(2));
+
+        tree.layout(Vec2::new(10, 1));
+        tree.on_event(Event::Key(Key::PageDown));
+        assert_eq!(tree.row(), Some(3));
+
+        // The viewport of an enclosing scroll view takes precedence
+        let mut scroll = tree.scrollable();
+        draw(&mut scroll, Vec2::new(10, 4));
+        assert_eq!(scroll.get_inner().page_size(), 3);
+        scroll.on_event(Event::Key(Key::PageDown));
+        assert_eq!(scroll.get_inner().row(), Some(6));
+    }
+
+    #[test]
+    fn test_accordion() {
+        use super::TreeView;
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let toggled = Arc::new(Mutex::new(Vec::new()));
+        let log = toggled.clone();
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, true, "a".to_string()),
+            (1, true, "a1".to_string()),
+            (2, false, "a2".to_string()),
+            (0, true, "b".to_string()),
+            (1, false, "b1".to_string()),
+            (0, true, "c".to_string()),
+            (1, false, "c1".to_string()),
+        ])
+        .accordion(true)
+        .on_collapse(move |_, row, is_collapsed, _| log.lock().unwrap().push((row, is_collapsed)));
+
+        let mut siv = cursive::Cursive::new();
+        tree.expand_item(0);
+        tree.expand_item(1);
+        tree.expand_item(4);
+        assert_eq!(tree.visible_height(), 6);
+
+        // Nested items are left untouched
+        tree.set_selected_row(3);
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(tree.visible_height(), 4);
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.is_collapsed(1), Some(false));
+        assert_eq!(
+            *toggled.lock().unwrap(),
+            vec![(0, true), (3, true), (1, false)]
+        );
+
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(tree.visible_height(), 3);
+
+        tree.set_accordion(false);
+        tree.set_selected_row(0);
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(tree.visible_height(), 5);
+    }
+
+    #[test]
+    fn test_children_provider_loads_once() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mut tree = TreeView::<String>::new().children_provider(move |_: &String| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Vec::new()
+        });
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+
+        // Containers without any children are only asked once
+        tree.on_event(Event::Key(Key::Enter));
+        tree.on_event(Event::Key(Key::Enter));
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(tree.len(), 1);
+
+        // Removing the children allows loading them again
+        tree.remove_children(0);
+        tree.collapse_item(0);
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_async_loader() {
+        use super::{AsyncLazyTree, ItemHandle, Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::mpsc::{channel, Sender};
+
+        struct Loader(Sender<ItemHandle>);
+
+        impl AsyncLazyTree<String> for Loader {
+            fn placeholder(&mut self, _: &String) -> String {
+                "Loading".to_string()
+            }
+
+            fn request_children(&mut self, _: &String, handle: ItemHandle) {
+                self.0.send(handle).unwrap();
+            }
+        }
+
+        let (tx, rx) = channel();
+        let mut tree = TreeView::<String>::new().async_loader(Loader(tx));
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(tree.borrow_item(1), Some(&"Loading".to_string()));
+        assert!(!tree.is_item_enabled(1));
+        let handle = rx.try_recv().unwrap();
+
+        // Children still arrive at the right item after it was collapsed
+        tree.on_event(Event::Key(Key::Enter));
+        tree.on_event(Event::Key(Key::Down));
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(
+            tree.replace_children(
+                &handle,
+                vec![("1.1".to_string(), false), ("1.2".to_string(), true)]
+            ),
+            Some(vec!["Loading".to_string()])
+        );
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.borrow_item(1), Some(&"2".to_string()));
+
+        tree.expand_item(0);
+        assert_eq!(tree.children_count(0), Some(2));
+        assert_eq!(tree.borrow_item(2), Some(&"1.2".to_string()));
+        assert_eq!(tree.is_collapsed(2), Some(true));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_builder() {
+        use super::{Placement, TreeBuilder, TreeView};
+
+        let mut tree = TreeView::from_builder(TreeBuilder::Node(
+            "1".to_string(),
+            vec![
+                TreeBuilder::Node(
+                    "1.1".to_string(),
+                    vec![TreeBuilder::Leaf("1.1.1".to_string())],
+                ),
+                TreeBuilder::Node("1.2".to_string(), Vec::new()),
+                TreeBuilder::Leaf("1.3".to_string()),
+            ],
+        ));
+        assert_eq!(
+            tree.to_level_vec(),
+            vec![
+                (0, true, &"1".to_string()),
+                (1, true, &"1.1".to_string()),
+                (2, false, &"1.1.1".to_string()),
+                (1, true, &"1.2".to_string()),
+                (1, false, &"1.3".to_string()),
+            ]
+        );
+        assert_eq!(tree.visible_height(), 5);
+        assert_eq!(tree.is_collapsed(3), Some(false));
+
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        tree.collapse_item(1);
+        assert_eq!(
+            tree.append_builder(
+                1,
+                TreeBuilder::Node(
+                    "1.1.2".to_string(),
+                    vec![TreeBuilder::Leaf("x".to_string())]
+                )
+            ),
+            None
+        );
+        assert_eq!(tree.children_count(1), Some(2));
+        assert_eq!(tree.descendant_count(1), Some(3));
+
+        assert_eq!(
+            tree.append_builder(4, TreeBuilder::Leaf("2.1".to_string())),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_push_root() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        assert_eq!(tree.push_root_container("1".to_string()), 0);
+        assert_eq!(tree.is_collapsed(0), Some(true));
+
+        tree.expand_item(0);
+        tree.insert_item("1.1".to_string(), Placement::LastChild, 0);
+        tree.insert_item("1.1.1".to_string(), Placement::LastChild, 1);
+        assert_eq!(tree.push_root("2".to_string()), 3);
+
+        tree.collapse_item(0);
+        assert_eq!(tree.push_root("3".to_string()), 2);
+        assert_eq!(
+            tree.to_level_vec(),
+            vec![
+                (0, true, &"1".to_string()),
+                (1, true, &"1.1".to_string()),
+                (2, false, &"1.1.1".to_string()),
+                (0, false, &"2".to_string()),
+                (0, false, &"3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_take_focus_direction() {
+        use super::{Placement, TreeView};
+        use cursive::direction::Direction;
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new();
+        for i in 0..5 {
+            tree.insert_item(i.to_string(), Placement::After, i);
+        }
+        tree.set_item_enabled(4, false);
+        tree.set_selected_row(2);
+
+        // Entering sideways keeps the selection
+        assert!(tree.take_focus(Direction::left()).is_ok());
+        assert_eq!(tree.row(), Some(2));
+
+        // Entering from below selects the last enabled row
+        assert!(tree.take_focus(Direction::down()).is_ok());
+        assert_eq!(tree.row(), Some(3));
+
+        // Entering from above selects the first row
+        assert!(tree.take_focus(Direction::up()).is_ok());
+        assert_eq!(tree.row(), Some(0));
+
+        // Entering via Tab keeps the selection
+        tree.set_selected_row(2);
+        assert!(tree.take_focus(Direction::front()).is_ok());
+        assert_eq!(tree.row(), Some(2));
+        assert!(tree.take_focus(Direction::back()).is_ok());
+        assert_eq!(tree.row(), Some(2));
+
+        tree.clear();
+        assert!(tree.take_focus(Direction::up()).is_err());
+    }
+
+    #[test]
+    fn test_submit_container() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let submitted = Arc::new(Mutex::new(Vec::new()));
+        let containers = submitted.clone();
+        let leaves = submitted.clone();
+        let mut tree = TreeView::<String>::new()
+            .on_submit_container(move |_, row| containers.lock().unwrap().push(("container", row)))
+            .on_submit(move |_, row| leaves.lock().unwrap().push(("leaf", row)));
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        tree.on_event(Event::Key(Key::Down));
+        tree.on_event(Event::Key(Key::Enter)).process(&mut siv);
+        assert_eq!(
+            *submitted.lock().unwrap(),
+            vec![("container", 0), ("leaf", 1)]
+        );
     }
 
-    /// Selects the row at the specified index.
-    pub fn set_selected_row(&mut self, row: usize) {
-        self.focus = row;
+    #[test]
+    fn test_row_offset() {
+        use super::{Placement, TreeView};
+        use cursive::view::View;
+        use cursive::Vec2;
+
+        let mut tree = TreeView::<String>::new().wrap(true);
+        tree.insert_item("a b c".to_string(), Placement::After, 0);
+        tree.insert_item("d".to_string(), Placement::After, 0);
+        tree.insert_item("e".to_string(), Placement::After, 1);
+        tree.layout(Vec2::new(4, 10));
+
+        assert_eq!(tree.row_offset(0), 0);
+        assert_eq!(tree.row_offset(1), 3);
+        assert_eq!(tree.row_offset(2), 4);
+        assert_eq!(tree.row_offset(10), 4);
     }
 
-    /// Selects the row at the specified index.
-    ///
-    /// Chainable variant.
-    pub fn selected_row(self, row: usize) -> Self {
-        self.with(|t| t.set_selected_row(row))
+    #[test]
+    fn test_collapse_focused_ancestor() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "1".to_string()),
+            (1, false, "1.1".to_string()),
+            (2, false, "1.1.1".to_string()),
+            (3, false, "1.1.1.1".to_string()),
+            (0, false, "2".to_string()),
+        ]);
+        tree.set_selected_row(3);
+
+        tree.collapse_item(1);
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.borrow_item(1), Some(&"1.1".to_string()));
+
+        // Items below keep the focus as their rows change
+        tree.expand_item(1);
+        tree.set_selected_row(4);
+        tree.collapse_item(0);
+        assert_eq!(tree.row(), Some(1));
+        tree.set_collapsed(0, false);
+        assert_eq!(tree.row(), Some(4));
     }
 
-    /// Returns a immutable reference to the item at the given row.
-    ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn borrow_item(&self, row: usize) -> Option<&T> {
-        let index = self.list.row_to_item_index(row);
-        self.list.get(index)
+    #[test]
+    fn test_on_edge() {
+        use super::{Edge, Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+        use std::sync::{Arc, Mutex};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        assert!(!tree.on_event(Event::Key(Key::Up)).is_consumed());
+
+        let edges = Arc::new(Mutex::new(Vec::new()));
+        let reached = edges.clone();
+        tree.set_on_edge(move |_, edge| reached.lock().unwrap().push(edge));
+
+        let mut siv = cursive::Cursive::new();
+        tree.on_event(Event::Key(Key::Up)).process(&mut siv);
+        tree.on_event(Event::Key(Key::Down)).process(&mut siv);
+        tree.on_event(Event::Key(Key::Down)).process(&mut siv);
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(*edges.lock().unwrap(), vec![Edge::Top, Edge::Bottom]);
     }
 
-    /// Returns a mutable reference to the item at the given row.
-    ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn borrow_item_mut(&mut self, row: usize) -> Option<&mut T> {
-        let index = self.list.row_to_item_index(row);
-        self.list.get_mut(index)
+    #[test]
+    fn test_scroll_view_follows_selection() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::{Scrollable, View};
+        use cursive::Vec2;
+
+        let mut scroll = TreeView::<String>::new().wrap(true).scrollable();
+        for i in 0..20 {
+            let item = if i == 19 { "a b c" } else { "a" };
+            scroll
+                .get_inner_mut()
+                .insert_item(item.to_string(), Placement::After, i);
+        }
+        scroll.layout(Vec2::new(5, 5));
+
+        // All lines of the wrapped last row are scrolled into view
+        scroll.on_event(Event::Key(Key::End));
+        scroll.layout(Vec2::new(5, 5));
+        let viewport = scroll.content_viewport();
+        assert_eq!(scroll.get_inner().row(), Some(19));
+        assert_eq!(viewport.bottom(), 21);
+
+        scroll.on_event(Event::Key(Key::Home));
+        assert_eq!(scroll.content_viewport().top(), 0);
     }
 
-    /// Inserts a new `item` at the given `row` with the specified
-    /// [`Placement`](enum.Placement.html), returning the visual row of the item
-    /// occupies after its insertion.
-    ///
-    ///
-    /// `None` will be returned in case the item is not visible after insertion
-    /// due to one of its parents being in a collapsed state.
-    pub fn insert_item(&mut self, item: T, placement: Placement, row: usize) -> Option<usize> {
-        let index = self.list.row_to_item_index(row);
-        self.list.insert_item(placement, index, item)
+    #[test]
+    fn test_wrap_navigation() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let mut tree = TreeView::<String>::new().wrap_navigation(true);
+        for i in 0..4 {
+            tree.insert_item(i.to_string(), Placement::After, i);
+        }
+        tree.set_item_enabled(3, false);
+
+        assert!(tree.on_event(Event::Key(Key::Up)).is_consumed());
+        assert_eq!(tree.row(), Some(2));
+        assert!(tree.on_event(Event::Key(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), Some(0));
+
+        // Paging does not wrap around
+        tree.on_event(Event::Key(Key::PageUp));
+        assert_eq!(tree.row(), Some(0));
+        tree.on_event(Event::Key(Key::PageDown));
+        tree.on_event(Event::Key(Key::PageDown));
+        assert_eq!(tree.row(), Some(2));
     }
 
-    /// Inserts a new `container` at the given `row` with the specified
-    /// [`Placement`](enum.Placement.html), returning the visual row of the
-    /// container occupies after its insertion.
-    ///
-    /// A container is identical to a normal item except for the fact that it
-    /// can always be collapsed even if it does not contain any children.
-    ///
-    /// > Note: If the container is not visible because one of its parents is
-    /// > collapsed `None` will be returned since there is no visible row for
-    /// > the container to occupy.
-    pub fn insert_container_item(
-        &mut self,
-        item: T,
-        placement: Placement,
-        row: usize,
-    ) -> Option<usize> {
-        let index = self.list.row_to_item_index(row);
-        self.list.insert_container_item(placement, index, item)
+    #[test]
+    fn test_first_and_last_row() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        assert_eq!(tree.first_row(), None);
+        assert_eq!(tree.last_row(), None);
+
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("1.1".to_string(), Placement::LastChild, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        assert_eq!(tree.first_row(), Some(0));
+        assert_eq!(tree.last_row(), Some(2));
+
+        tree.collapse_item(0);
+        assert_eq!(tree.last_row(), Some(1));
     }
 
-    /// Removes the item at the given `row` along with all of its children.
-    ///
-    /// The returned vector contains the removed items in top to bottom order.
-    ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn remove_item(&mut self, row: usize) -> Option<Vec<T>> {
-        let index = self.list.row_to_item_index(row);
-        let removed = self.list.remove_with_children(index);
-        self.focus = cmp::min(self.focus, self.list.height() - 1);
-        removed
+    #[test]
+    fn test_empty_after_removal() {
+        use super::{Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use cursive::view::View;
+
+        let removals: [fn(&mut TreeView<String>); 3] = [
+            |tree| {
+                tree.remove_item(0);
+            },
+            |tree| {
+                tree.remove_children(0);
+                tree.remove_item(0);
+            },
+            |tree| {
+                tree.extract_item(0);
+            },
+        ];
+
+        for (i, remove) in removals.iter().enumerate() {
+            let mut tree = TreeView::<String>::new()
+                .on_edge(|_, _| panic!("edge reached"))
+                .wrap_navigation(i == 0);
+            tree.insert_item("1".to_string(), Placement::After, 0);
+            remove(&mut tree);
+            assert!(tree.is_empty());
+            assert_eq!(tree.row(), None);
+
+            let mut siv = cursive::Cursive::new();
+            for key in [Key::Up, Key::Down, Key::End, Key::Home, Key::Enter].iter() {
+                let result = tree.on_event(Event::Key(*key));
+                assert!(!result.is_consumed());
+                result.process(&mut siv);
+            }
+            assert!(!tree.on_event(Event::Shift(Key::Down)).is_consumed());
+            assert!(!tree.on_event(Event::Char('*')).is_consumed());
+            assert_eq!(tree.row(), None);
+        }
     }
 
-    /// Removes all children of the item at the given `row`.
-    ///
-    /// The returned vector contains the removed children in top to bottom order.
-    ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn remove_children(&mut self, row: usize) -> Option<Vec<T>> {
-        let index = self.list.row_to_item_index(row);
-        let removed = self.list.remove_children(index);
-        self.focus = cmp::min(self.focus, self.list.height() - 1);
-        removed
+    #[test]
+    fn test_selected_row_clamped() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new().selected_row(3);
+        assert_eq!(tree.row(), None);
+
+        tree.insert_item("1".to_string(), Placement::After, 0);
+        tree.insert_item("1.1".to_string(), Placement::LastChild, 0);
+        tree.insert_item("2".to_string(), Placement::After, 0);
+        tree.set_selected_row(10);
+        assert_eq!(tree.row(), Some(2));
+
+        tree.collapse_item(0);
+        tree.set_selected_row(2);
+        assert_eq!(tree.row(), Some(1));
     }
 
-    /// Extracts the item at the given `row` from the tree.
-    ///
-    /// All of the items children will be moved up one level within the tree.
-    ///
-    /// `None` is returned in case the specified `row` does not visually exist.
-    pub fn extract_item(&mut self, row: usize) -> Option<T> {
-        let index = self.list.row_to_item_index(row);
-        let removed = self.list.remove(index);
-        self.focus = cmp::min(self.focus, self.list.height() - 1);
-        removed
+    #[test]
+    fn test_borrowed_items() {
+        use super::{Placement, TreeView};
+
+        // Items borrowing from a local value can be used outside of a view
+        let text = String::from("src lib.rs main.rs");
+        let mut names = text.split(' ');
+
+        let mut tree = TreeView::<&str>::new();
+        tree.insert_item(names.next().unwrap(), Placement::After, 0);
+        for name in names {
+            tree.insert_item(name, Placement::LastChild, 0);
+        }
+        tree.set_item_style_callback(|_: &&str, _, _| Default::default());
+
+        assert_eq!(tree.path_to(2), Some(vec![&"src", &"main.rs"]));
+        tree.collapse_item(0);
+        assert_eq!(tree.visible_height(), 1);
+        assert_eq!(tree.take_items(), vec!["src", "lib.rs", "main.rs"]);
     }
 
-    /// Collapses the children of the given `row`.
-    pub fn collapse_item(&mut self, row: usize) {
-        let index = self.list.row_to_item_index(row);
-        self.list.set_collapsed(index, true);
+    #[test]
+    fn test_focus_follows_item() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "a.1".to_string()),
+            (0, false, "b".to_string()),
+            (0, false, "c".to_string()),
+        ]);
+        tree.set_selected_row(2);
+
+        // Mutations above the selection
+        tree.insert_item("0".to_string(), Placement::Before, 0);
+        assert_eq!(
+            tree.borrow_item(tree.row().unwrap()),
+            Some(&"b".to_string())
+        );
+        tree.insert_items(
+            vec!["a.2".to_string(), "a.3".to_string()],
+            Placement::After,
+            2,
+        );
+        assert_eq!(tree.row(), Some(5));
+        tree.remove_item(0);
+        tree.extract_item(0);
+        tree.remove_children(0);
+        assert_eq!(tree.row(), Some(3));
+        assert_eq!(tree.borrow_item(3), Some(&"b".to_string()));
+
+        // Mutations below the selection
+        tree.insert_item("c.1".to_string(), Placement::LastChild, 4);
+        tree.remove_item(4);
+        assert_eq!(tree.row(), Some(3));
+
+        // Removing the selected item keeps the row
+        tree.insert_item("d".to_string(), Placement::After, 3);
+        tree.remove_item(3);
+        assert_eq!(tree.row(), Some(3));
+        assert_eq!(tree.borrow_item(3), Some(&"d".to_string()));
     }
 
-    /// Expands the children of the given `row`.
-    pub fn expand_item(&mut self, row: usize) {
-        let index = self.list.row_to_item_index(row);
-        self.list.set_collapsed(index, false);
+    #[test]
+    fn test_swap_with_sibling() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "a.1".to_string()),
+            (0, false, "b".to_string()),
+            (1, false, "b.1".to_string()),
+            (0, false, "c".to_string()),
+        ]);
+        tree.set_selected_row(2);
+
+        assert!(tree.swap_with_next_sibling(0));
+        assert_eq!(tree.path_string(1, "/"), Some("b/b.1".to_string()));
+        assert_eq!(tree.path_string(3, "/"), Some("a/a.1".to_string()));
+        assert_eq!(tree.row(), Some(0));
+
+        assert!(tree.swap_with_next_sibling(2));
+        assert!(!tree.swap_with_next_sibling(4));
+        assert!(!tree.swap_with_previous_sibling(1));
+        assert!(!tree.swap_with_previous_sibling(10));
+        assert_eq!(tree.borrow_item(3), Some(&"a".to_string()));
+        assert_eq!(tree.row(), Some(0));
     }
 
-    /// Collapses or expands the children of the given `row`.
-    pub fn set_collapsed(&mut self, row: usize, collapsed: bool) {
-        let index = self.list.row_to_item_index(row);
-        self.list.set_collapsed(index, collapsed);
+    #[test]
+    fn test_wrap_layout() {
+        use super::{Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new().wrap(true);
+        tree.insert_item("alpha beta".to_string(), Placement::After, 0);
+        tree.insert_item("gamma delta".to_string(), Placement::LastChild, 0);
+        tree.insert_item("eps".to_string(), Placement::After, 0);
+        tree.expand_item(0);
+
+        // Continuation lines are indented to the text of their item
+        let buffer = draw(&mut tree, Vec2::new(10, 6));
+        assert_eq!(
+            lines(&buffer),
+            vec!["▾ alpha", "  beta", "  ◦ gamma", "    delta", "◦ eps", ""]
+        );
+        assert_eq!(tree.row_offset(1), 2);
+        assert_eq!(tree.row_offset(2), 4);
+
+        // Without wrapping every item takes a single line
+        tree.set_wrap(false);
+        let buffer = draw(&mut tree, Vec2::new(20, 3));
+        assert_eq!(
+            lines(&buffer),
+            vec!["▾ alpha beta", "  ◦ gamma delta", "◦ eps"]
+        );
+        assert_eq!(tree.row_offset(2), 2);
     }
 
-    /// Collapses or expands the children of the given `row`.
-    ///
-    /// Chained variant.
-    pub fn collapsed(self, row: usize, collapsed: bool) -> Self {
-        self.with(|t| t.set_collapsed(row, collapsed))
+    #[test]
+    fn test_vetoed_expand_does_not_load() {
+        use super::{AsyncLazyTree, ItemHandle, Placement, TreeView};
+        use cursive::event::{Event, Key};
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Loader(Arc<AtomicUsize>);
+
+        impl AsyncLazyTree<String> for Loader {
+            fn placeholder(&mut self, _: &String) -> String {
+                "Loading...".to_string()
+            }
+
+            fn request_children(&mut self, _: &String, _: ItemHandle) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let allowed = Arc::new(AtomicBool::new(false));
+        let counter = calls.clone();
+        let is_allowed = allowed.clone();
+        let mut tree = TreeView::<String>::new()
+            .children_provider(move |_: &String| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                vec![("child".to_string(), false)]
+            })
+            .on_pre_collapse(move |_, _, _| is_allowed.load(Ordering::SeqCst));
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert!(!tree.list.is_loaded(0));
+        assert_eq!(tree.len(), 1);
+
+        allowed.store(true, Ordering::SeqCst);
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(tree.len(), 2);
+
+        // Neither is a placeholder inserted for a cancelled expand
+        let requests = Arc::new(AtomicUsize::new(0));
+        let mut tree = TreeView::<String>::new()
+            .async_loader(Loader(requests.clone()))
+            .on_before_expand(|_, _| false);
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+
+        tree.on_event(Event::Key(Key::Enter));
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert_eq!(tree.len(), 1);
     }
 
-    /// Select item `n` rows up from the one currently selected.
-    pub fn focus_up(&mut self, n: usize) {
-        self.focus -= cmp::min(self.focus, n);
+    #[test]
+    fn test_insert_subtree_handles() {
+        use super::{ExtractedTree, Placement, TreeView};
+
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::After, 0);
+        let a = tree.handle(0).unwrap();
+        let b = tree.handle(1).unwrap();
+
+        tree.remove_item(0);
+        assert_eq!(tree.row_for_handle(&a), None);
+
+        // Items of foreign subtrees never take over the handles of removed ones
+        let foreign = ExtractedTree::from_levels(vec![(0, false, "foreign".to_string())]);
+        tree.insert_subtree(foreign, Placement::After, 0);
+        assert_eq!(tree.row_for_handle(&a), None);
+        assert_eq!(tree.borrow_item_by_handle(&a), None);
+        assert_eq!(tree.row_for_handle(&b), Some(0));
+
+        let subtree = tree.remove_subtree(0).unwrap();
+        tree.insert_subtree(subtree, Placement::After, 0);
+        assert_eq!(tree.row_for_handle(&b), None);
+        assert_eq!(tree.handle(1).map(|handle| handle == b), Some(false));
     }
 
-    /// Select item `n` rows down from the one currently selected.
-    pub fn focus_down(&mut self, n: usize) {
-        self.focus = cmp::min(self.focus + n, self.list.height() - 1);
+    #[test]
+    fn test_item_style_callback() {
+        use super::{Placement, TreeView};
+        use cursive::theme::{Effect, Style};
+
+        let mut tree = TreeView::<String>::new().item_style_callback(|item: &String, _, _| {
+            if item == "b" {
+                Style::from(Effect::Bold).combine(ColorStyle::title_primary())
+            } else {
+                Style::default()
+            }
+        });
+        tree.insert_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::After, 0);
+
+        // Both the symbol and the text are styled
+        let buffer = draw(&mut tree, Vec2::new(10, 2));
+        assert_eq!(lines(&buffer), vec!["◦ a", "◦ b"]);
+        for &x in &[0, 2] {
+            let style = buffer.cell_style(Vec2::new(x, 1)).unwrap();
+            assert_eq!(style.color, colors(ColorStyle::title_primary()));
+            assert!(style.effects.contains(Effect::Bold));
+        }
+        let style = buffer.cell_style(Vec2::new(2, 0)).unwrap();
+        assert!(!style.effects.contains(Effect::Bold));
+
+        // The focused row keeps the highlight along with the effects
+        tree.set_selected_row(1);
+        let buffer = draw(&mut tree, Vec2::new(10, 2));
+        let style = buffer.cell_style(Vec2::new(2, 1)).unwrap();
+        assert_eq!(style.color, colors(ColorStyle::highlight()));
+        assert!(style.effects.contains(Effect::Bold));
     }
 
-    /// Returns position of the parent of the item located in `row`.
-    ///
-    /// `None` is returned if `row` is not currenlty visible or if the item has no ancestors.
-    pub fn item_parent(&self, row: usize) -> Option<usize> {
-        let item_index = self.list.row_to_item_index(row);
-        let parent_index = self.list.item_parent_index(item_index)?;
-        Some(self.list.item_index_to_row(parent_index))
+    #[test]
+    fn test_indent_guides_filtered() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "match".to_string()),
+            (2, false, "b".to_string()),
+            (2, false, "match".to_string()),
+            (2, false, "c".to_string()),
+            (1, false, "d".to_string()),
+            (0, false, "e".to_string()),
+        ])
+        .indent_guides(true)
+        .filter(Some("match".to_string()));
+
+        // Siblings hidden by the filter do not continue the guides
+        let buffer = draw(&mut tree, Vec2::new(20, 4));
+        assert_eq!(lines(&buffer), vec!["▾ a", "└─▾ match", "  └─◦ match", ""]);
     }
 
-    fn submit(&mut self) -> EventResult {
-        let row = self.focus;
-        let index = self.list.row_to_item_index(row);
+    #[test]
+    fn test_full_row_highlight() {
+        use super::{Placement, TreeView};
 
-        if self.list.is_container_item(index) {
-            let collapsed = self.list.get_collapsed(index);
-            let children = self.list.get_children(index);
+        let mut tree = TreeView::<String>::new();
+        tree.insert_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::After, 0);
+        let highlight = colors(ColorStyle::highlight());
 
-            self.list.set_collapsed(index, !collapsed);
+        let buffer = draw(&mut tree, Vec2::new(6, 2));
+        for x in 0..6 {
+            assert_eq!(buffer.cell_style(Vec2::new(x, 0)).unwrap().color, highlight);
+        }
+        assert_ne!(
+            buffer.cell_style(Vec2::new(2, 1)).map(|s| s.color),
+            Some(highlight)
+        );
 
-            if self.on_collapse.is_some() {
-                let cb = self.on_collapse.clone().unwrap();
-                return EventResult::Consumed(Some(Callback::from_fn(move |s| {
-                    cb(s, row, !collapsed, children)
-                })));
-            }
-        } else if self.on_submit.is_some() {
-            let cb = self.on_submit.clone().unwrap();
-            return EventResult::Consumed(Some(Callback::from_fn(move |s| cb(s, row))));
+        // Only the text of the item is highlighted
+        tree.set_full_row_highlight(false);
+        let buffer = draw(&mut tree, Vec2::new(6, 2));
+        assert_eq!(buffer.cell_style(Vec2::new(2, 0)).unwrap().color, highlight);
+        for &x in &[0, 3, 5] {
+            assert_ne!(
+                buffer.cell_style(Vec2::new(x, 0)).map(|s| s.color),
+                Some(highlight)
+            );
         }
+    }
 
-        EventResult::Ignored
+    #[test]
+    fn test_row_colors() {
+        use super::{Placement, TreeView};
+        use cursive::theme::{BaseColor, Color, PaletteColor};
+
+        let stripe = ColorStyle::new(PaletteColor::Primary, Color::Dark(BaseColor::Blue));
+        let selection =
+            ColorStyle::new(Color::Dark(BaseColor::Red), Color::Dark(BaseColor::Yellow));
+        let inactive = ColorStyle::new(Color::Dark(BaseColor::Red), Color::Dark(BaseColor::Cyan));
+        let mut tree = TreeView::<String>::new()
+            .row_colors(ColorStyle::primary(), Some(stripe))
+            .selection_style(selection)
+            .inactive_selection_style(inactive);
+        for i in 0..4 {
+            tree.insert_item(i.to_string(), Placement::After, i);
+        }
+
+        // Odd rows are striped across the full width
+        let buffer = draw(&mut tree, Vec2::new(8, 4));
+        for &x in &[0, 2, 5] {
+            let color = |row| buffer.cell_style(Vec2::new(x, row)).map(|s| s.color);
+            assert_eq!(color(0), Some(colors(selection)));
+            assert_eq!(color(1), Some(colors(stripe)));
+            assert_eq!(color(3), Some(colors(stripe)));
+        }
+        assert_eq!(
+            buffer.cell_style(Vec2::new(2, 2)).unwrap().color,
+            colors(ColorStyle::primary())
+        );
+
+        tree.disable();
+        let buffer = draw(&mut tree, Vec2::new(8, 4));
+        assert_eq!(
+            buffer.cell_style(Vec2::new(2, 0)).unwrap().color,
+            colors(inactive)
+        );
     }
-}
 
-impl<T: Display + Send + Sync + Debug + 'static> View for TreeView<T> {
-    fn draw(&self, printer: &Printer<'_, '_>) {
-        let index = self.list.row_to_item_index(0);
-        let items = self.list.items();
-        let list_index = Arc::new(Mutex::new(index));
+    #[test]
+    fn test_typeahead_star() {
+        use super::{Placement, TreeView, TypeaheadMode};
+        use cursive::event::Event;
 
-        for i in 0..self.list.height() {
-            let printer = printer.offset((0, i));
-            let mut index = list_index.lock().unwrap();
+        let mut tree = TreeView::<String>::new().typeahead(TypeaheadMode::Prefix);
+        tree.insert_container_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::LastChild, 0);
+        tree.insert_item("a*b".to_string(), Placement::After, 0);
 
-            let item = &items[*index];
-            *index += item.len();
+        // Without a pending query the subtree is toggled
+        tree.on_event(Event::Char('*'));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        tree.collapse_item(0);
 
-            let color = if i == self.focus {
-                if self.enabled && printer.focused {
-                    ColorStyle::highlight()
-                } else {
-                    ColorStyle::highlight_inactive()
-                }
-            } else {
-                ColorStyle::primary()
-            };
+        tree.on_event(Event::Char('a'));
+        tree.on_event(Event::Char('*'));
+        assert_eq!(tree.search_query(), "a*");
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.is_collapsed(0), Some(true));
+    }
 
-            printer.print((item.offset(), 0), item.symbol());
+    #[test]
+    fn test_expand_all_keeps_focus() {
+        use super::TreeView;
 
-            printer.with_color(color, |printer| {
-                printer.print(
-                    (item.offset() + SYMBOL_WIDTH, 0),
-                    format!("{}", item.value()).as_str(),
-                );
-            });
-        }
+        let levels = vec![
+            (0, false, "a".to_string()),
+            (1, false, "a.1".to_string()),
+            (0, false, "b".to_string()),
+            (0, false, "c".to_string()),
+            (1, false, "c.1".to_string()),
+        ];
+        let mut tree = TreeView::<String>::from_level_vec(levels.clone());
+        tree.set_selected_row(2);
+
+        // Branches above and below the selection
+        tree.collapse_descendants(0);
+        assert_eq!(
+            tree.borrow_item(tree.row().unwrap()),
+            Some(&"b".to_string())
+        );
+        tree.expand_descendants(0);
+        assert_eq!(tree.row(), Some(2));
+        tree.collapse_descendants(3);
+        assert_eq!(tree.row(), Some(2));
+
+        tree.collapse_all();
+        assert_eq!(tree.row(), Some(1));
+        tree.expand_all();
+        assert_eq!(tree.row(), Some(2));
+
+        let mut tree = TreeView::<String>::from_level_vec(levels).collapsed_all();
+        tree.set_selected_row(1);
+        tree.expand_all();
+        assert_eq!(
+            tree.borrow_item(tree.row().unwrap()),
+            Some(&"b".to_string())
+        );
     }
 
-    fn required_size(&mut self, _req: Vec2) -> Vec2 {
-        let w: usize = self
-            .list
-            .items()
-            .iter()
-            .map(|item| item.level() * 2 + format!("{}", item.value()).len() + 2)
-            .max()
-            .unwrap_or(0);
+    #[test]
+    fn test_collapse_all_focused_ancestor() {
+        use super::TreeView;
 
-        let h = self.list.height();
+        let levels = vec![
+            (0, false, "a".to_string()),
+            (1, false, "a.1".to_string()),
+            (2, false, "a.1.1".to_string()),
+            (0, false, "b".to_string()),
+            (0, false, "c".to_string()),
+        ];
+        let mut tree = TreeView::<String>::from_level_vec(levels.clone());
+        tree.set_selected_row(1);
+        tree.collapse_all();
+        assert_eq!(tree.row(), Some(0));
 
-        (w, h).into()
+        let mut tree = TreeView::<String>::from_level_vec(levels);
+        tree.set_selected_row(2);
+        tree.collapse_descendants(0);
+        assert_eq!(tree.row(), Some(0));
+        tree.expand_item(0);
+        assert_eq!(tree.row(), Some(0));
+        assert_eq!(tree.is_collapsed(1), Some(true));
     }
 
-    fn layout(&mut self, size: Vec2) {
-        self.last_size = size;
+    #[test]
+    fn test_indent_guide_style() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (1, false, "c".to_string()),
+        ])
+        .indent_guides(true);
+
+        // Guides are dimmed, unlike the text of their row
+        let buffer = draw(&mut tree, Vec2::new(10, 3));
+        assert_eq!(lines(&buffer), vec!["▾ a", "├─◦ b", "└─◦ c"]);
+        let front = |row| buffer.cell_style(Vec2::new(0, row)).unwrap().color.front;
+        assert_eq!(front(1), colors(ColorStyle::secondary()).front);
+        assert_eq!(front(2), colors(ColorStyle::secondary()).front);
+        assert_eq!(
+            buffer.cell_style(Vec2::new(4, 1)).unwrap().color,
+            colors(ColorStyle::primary())
+        );
+
+        // Except when they are part of the highlight of the focused row
+        tree.set_selected_row(1);
+        let buffer = draw(&mut tree, Vec2::new(10, 3));
+        assert_eq!(
+            buffer.cell_style(Vec2::new(0, 1)).unwrap().color,
+            colors(ColorStyle::highlight())
+        );
+        assert_eq!(
+            buffer.cell_style(Vec2::new(0, 2)).unwrap().color.front,
+            colors(ColorStyle::secondary()).front
+        );
     }
 
-    fn take_focus(&mut self, _: Direction) -> Result<EventResult, CannotFocus> {
-        (self.enabled && !self.is_empty())
-            .then(EventResult::consumed)
-            .ok_or(CannotFocus)
+    #[test]
+    fn test_child_rows() {
+        use super::TreeView;
+
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "b.1".to_string()),
+            (2, false, "b.2".to_string()),
+            (1, false, "c".to_string()),
+            (0, false, "d".to_string()),
+        ]);
+        assert_eq!(tree.child_rows(0), vec![1, 4]);
+
+        // Grandchildren hidden by a collapsed child are skipped
+        tree.collapse_item(1);
+        assert_eq!(tree.child_rows(0), vec![1, 2]);
+        assert!(tree.child_rows(1).is_empty());
+        assert!(tree.child_rows(3).is_empty());
+        assert!(tree.child_rows(10).is_empty());
     }
 
-    fn on_event(&mut self, event: Event) -> EventResult {
-        if !self.enabled {
-            return EventResult::Ignored;
-        }
+    #[test]
+    fn test_sibling_navigation_filtered() {
+        use super::TreeView;
+        use cursive::event::{Event, Key};
 
-        let last_focus = self.focus;
-        match event {
-            Event::Key(Key::Up) if self.focus > 0 => {
-                self.focus_up(1);
-            }
-            Event::Key(Key::Down) if self.focus + 1 < self.list.height() => {
-                self.focus_down(1);
-            }
-            Event::Key(Key::PageUp) => {
-                self.focus_up(10);
-            }
-            Event::Key(Key::PageDown) => {
-                self.focus_down(10);
-            }
-            Event::Key(Key::Home) => {
-                self.focus = 0;
-            }
-            Event::Key(Key::End) => {
-                self.focus = self.list.height() - 1;
-            }
-            Event::Key(Key::Enter) => {
-                if !self.is_empty() {
-                    return self.submit();
-                }
-            }
-            Event::Mouse {
-                position,
-                offset,
-                event: MouseEvent::Press(btn),
-            } => {
-                if let Some(position) = position.checked_sub(offset) {
-                    match position.y {
-                        y if y == self.focus && btn == MouseButton::Left => return self.submit(),
-                        y if y < self.list.height() => self.focus = position.y,
-                        _ => return EventResult::Ignored,
-                    }
-                }
-            }
-            _ => return EventResult::Ignored,
-        }
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, false, "root".to_string()),
+            (1, false, "match1".to_string()),
+            (1, false, "hidden".to_string()),
+            (1, false, "match2".to_string()),
+            (1, false, "other".to_string()),
+            (0, false, "match3".to_string()),
+        ])
+        .filter(Some("match".to_string()));
+        tree.set_selected_row(1);
+
+        // Hidden siblings are skipped
+        assert!(tree.on_event(Event::Shift(Key::Down)).is_consumed());
+        assert_eq!(
+            tree.borrow_item(tree.row().unwrap()),
+            Some(&"match2".to_string())
+        );
+
+        // Only hidden siblings remain
+        assert!(!tree.on_event(Event::Shift(Key::Down)).is_consumed());
+        assert_eq!(
+            tree.borrow_item(tree.row().unwrap()),
+            Some(&"match2".to_string())
+        );
+        assert!(tree.on_event(Event::Shift(Key::Up)).is_consumed());
+        assert_eq!(tree.row(), Some(1));
+
+        // Selecting siblings programmatically or via Ctrl behaves the same
+        let match2 = tree.select_next_sibling();
+        assert_eq!(
+            tree.borrow_item(match2.unwrap()),
+            Some(&"match2".to_string())
+        );
+        assert_eq!(tree.select_next_sibling(), match2);
+        assert!(!tree.on_event(Event::Ctrl(Key::Down)).is_consumed());
+        assert_eq!(tree.row(), match2);
+        assert!(tree.on_event(Event::Ctrl(Key::Up)).is_consumed());
+        assert_eq!(tree.row(), Some(1));
+        assert_eq!(tree.select_prev_sibling(), Some(1));
+        assert!(!tree.on_event(Event::Ctrl(Key::Up)).is_consumed());
+    }
 
-        let focus = self.focus;
+    #[test]
+    fn test_item_renderer_text() {
+        use super::{Placement, TreeView, Truncate};
+        use cursive::theme::{Effect, Style};
+        use cursive::utils::markup::StyledString;
+        use cursive::utils::span::{IndexedCow, IndexedSpan};
 
-        if !self.is_empty() && last_focus != focus {
-            let row = self.focus;
-            EventResult::Consumed(
-                self.on_select
-                    .clone()
-                    .map(|cb| Callback::from_fn(move |s| cb(s, row))),
+        // Markup outside of the spans is not part of the rendered text
+        let render = |item: &String| {
+            let source = format!("**{}** (new)", item);
+            let span = |start: usize, end: usize, attr: Style| IndexedSpan {
+                content: IndexedCow::Borrowed { start, end },
+                attr,
+                width: end - start,
+            };
+            let len = item.len();
+            StyledString::with_spans(
+                source.as_str(),
+                vec![
+                    span(2, 2 + len, Effect::Bold.into()),
+                    span(4 + len, source.len(), Style::default()),
+                ],
             )
-        } else {
-            EventResult::Ignored
-        }
+        };
+        let mut tree = TreeView::<String>::new().item_renderer(render);
+        tree.insert_item("a".to_string(), Placement::After, 0);
+        tree.insert_item("b".to_string(), Placement::After, 0);
+
+        let buffer = draw(&mut tree, Vec2::new(12, 2));
+        assert_eq!(lines(&buffer), vec!["◦ a (new)", "◦ b (new)"]);
+
+        tree.set_truncation(Truncate::End);
+        let buffer = draw(&mut tree, Vec2::new(6, 2));
+        assert_eq!(lines(&buffer), vec!["◦ a (…", "◦ b (…"]);
+
+        // Wrapped rows contain the rendered text and match the required size
+        tree.set_truncation(Truncate::None);
+        tree.set_wrap(true);
+        assert_eq!(tree.required_size(Vec2::new(8, 10)), Vec2::new(8, 4));
+        let buffer = draw(&mut tree, Vec2::new(8, 4));
+        assert_eq!(lines(&buffer), vec!["◦ a", "  (new)", "◦ b", "  (new)"]);
+        assert!(buffer
+            .cell_style(Vec2::new(2, 2))
+            .unwrap()
+            .effects
+            .contains(Effect::Bold));
     }
+    #[test]
+    fn test_toggle_descendants_before_expand() {
+        use super::{Placement, TreeView};
+        use cursive::event::Event;
+        use cursive::view::View;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
 
-    fn important_area(&self, size: Vec2) -> Rect {
-        Rect::from_size((0, self.focus), (size.x, 1))
+        let allow = Arc::new(AtomicBool::new(false));
+        let allowed = allow.clone();
+        let mut tree = TreeView::<String>::from_level_vec(vec![
+            (0, true, "a".to_string()),
+            (1, false, "b".to_string()),
+        ])
+        .on_before_expand(move |_, _| allowed.load(Ordering::SeqCst));
+        tree.collapse_item(0);
+
+        assert!(tree.on_event(Event::Char('*')).is_consumed());
+        assert_eq!(tree.is_collapsed(0), Some(true));
+
+        allow.store(true, Ordering::SeqCst);
+        tree.on_event(Event::Char('*'));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+
+        // Collapsing does not ask for permission
+        allow.store(false, Ordering::SeqCst);
+        tree.on_event(Event::Char('*'));
+        assert_eq!(tree.is_collapsed(0), Some(true));
+
+        // Unloaded containers load their children first
+        let mut tree = TreeView::<String>::new()
+            .lazy_loader(|item: &String| vec![(format!("{}.1", item), false)]);
+        tree.insert_container_item("1".to_string(), Placement::After, 0);
+        tree.on_event(Event::Char('*'));
+        assert_eq!(tree.is_collapsed(0), Some(false));
+        assert_eq!(tree.borrow_item(1), Some(&"1.1".to_string()));
     }
 }
@@ -1,9 +1,17 @@
 // STD Dependencies -----------------------------------------------------------
-use std::cmp;
+use std::cmp::{self, Ordering};
+use std::collections::HashSet;
 use std::fmt::{Debug, Display};
+use std::iter;
+use std::mem;
+use std::ops::Range;
+
+// External Dependencies ------------------------------------------------------
+use cursive::utils::lines::simple::simple_prefix;
 
 #[derive(Debug)]
 pub struct TreeNode<T: Display + Debug> {
+    id: usize,
     value: T,
     level: usize,
     is_collapsed: bool,
@@ -11,9 +19,38 @@ pub struct TreeNode<T: Display + Debug> {
     height: usize,
     is_container: bool,
     collapsed_height: Option<usize>,
+    is_selected: bool,
+    is_enabled: bool,
+    is_checked: bool,
+    is_filtered: bool,
+
+    /// Whether the children of the item were requested from a lazy loader
+    is_loaded: bool,
 }
 
 impl<T: Display + Debug> TreeNode<T> {
+    fn new(id: usize, value: T, is_container: bool) -> Self {
+        Self {
+            id,
+            value,
+            is_collapsed: is_container,
+            level: 0,
+            children: 0,
+            height: 1,
+            is_container,
+            collapsed_height: if is_container { Some(1) } else { None },
+            is_selected: false,
+            is_enabled: true,
+            is_checked: false,
+            is_filtered: false,
+            is_loaded: false,
+        }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
     pub fn value(&self) -> &T {
         &self.value
     }
@@ -22,6 +59,18 @@ impl<T: Display + Debug> TreeNode<T> {
         self.level
     }
 
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_selected(&self) -> bool {
+        self.is_selected
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
     pub fn len(&self) -> usize {
         if self.is_collapsed {
             self.children + 1
@@ -30,16 +79,12 @@ impl<T: Display + Debug> TreeNode<T> {
         }
     }
 
-    pub fn symbol(&self) -> &str {
-        if self.is_container {
-            if self.is_collapsed {
-                "▸"
-            } else {
-                "▾"
-            }
-        } else {
-            "◦"
-        }
+    pub fn is_container(&self) -> bool {
+        self.is_container
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.is_collapsed
     }
 
     /// Returns indentation of the element in the tree
@@ -47,14 +92,18 @@ impl<T: Display + Debug> TreeNode<T> {
         self.level() * 2
     }
 
-    /// Returns length of the string representation of the item
+    /// Returns the display width of the string representation of the item
     pub fn width(&self) -> usize {
-        format!("{}", self.value()).len()
+        simple_prefix(&format!("{}", self.value()), usize::MAX).width
     }
 }
 
 /// Determines how items are inserted into a [`TreeView`](struct.TreeView.html).
+///
+/// New placements may be added in the future, so matching on this enum
+/// requires a wildcard arm.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+#[non_exhaustive]
 pub enum Placement {
     /// The item is inserted as a sibling after the specified row.
     After,
@@ -70,14 +119,142 @@ pub enum Placement {
     /// after all other existing children.
     LastChild,
 
+    /// The item is inserted as new child of the specified row, placed at the
+    /// given position among the existing children.
+    ///
+    /// In case the position exceeds the number of children, the item is
+    /// placed after all of them.
+    NthChild(usize),
+
     /// The item is inserted as the new immediate parent of the specified row.
     Parent,
 }
 
+/// The state of an item's checkbox, see
+/// [`TreeView::set_checkboxes`](struct.TreeView.html#method.set_checkboxes).
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum CheckState {
+    /// Neither the item nor any of its descendants are checked.
+    Unchecked,
+
+    /// Some, but not all descendants of the item are checked.
+    Partial,
+
+    /// The item and all of its descendants are checked.
+    Checked,
+}
+
+/// Determines what happens to the children of items removed via
+/// [`TreeView::retain`](struct.TreeView.html#method.retain).
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum RetainPolicy {
+    /// The children are removed along with the item, like
+    /// [`remove_item`](struct.TreeView.html#method.remove_item) does.
+    RemoveChildren,
+
+    /// The children are moved up one level in place of the item, like
+    /// [`extract_item`](struct.TreeView.html#method.extract_item) does.
+    ExtractChildren,
+}
+
+/// An opaque handle referring to a single item within a
+/// [`TreeView`](struct.TreeView.html).
+///
+/// Unlike rows, handles are not affected by insertions, removals or changes
+/// to the collapsed state of other items. Once the item has been removed
+/// from the tree, all methods taking its handle return `None`.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct ItemHandle(pub(crate) usize);
+
+/// A subtree removed from a [`TreeView`](struct.TreeView.html) via
+/// [`remove_subtree`](struct.TreeView.html#method.remove_subtree).
+///
+/// The structure, collapsed state and container flags of all items are kept
+/// intact, so the subtree can be inserted again via
+/// [`insert_subtree`](struct.TreeView.html#method.insert_subtree).
+#[derive(Debug)]
+pub struct ExtractedTree<T: Display + Debug> {
+    pub(crate) nodes: Vec<TreeNode<T>>,
+}
+
+impl<T: Display + Debug> ExtractedTree<T> {
+    /// Returns the number of items within the subtree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the subtree contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over all items of the subtree in top to bottom
+    /// order, along with their level relative to the root of the subtree.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.nodes.iter().map(|node| (node.level, &node.value))
+    }
+
+    /// Consumes the subtree, returning its items in top to bottom order.
+    pub fn into_items(self) -> Vec<T> {
+        self.nodes.into_iter().map(|node| node.value).collect()
+    }
+
+    /// Consumes the subtree, returning `(level, is_container, item)` triples
+    /// in top to bottom order, with levels relative to the root of the
+    /// subtree.
+    ///
+    /// The collapsed state of the items is not part of the returned triples.
+    pub fn into_levels(self) -> Vec<(usize, bool, T)> {
+        self.nodes
+            .into_iter()
+            .map(|node| (node.level, node.is_container, node.value))
+            .collect()
+    }
+
+    /// Creates a new subtree from `(level, is_container, item)` triples in
+    /// top to bottom order, as returned by
+    /// [`into_levels`](#method.into_levels), with the root at level `0`.
+    ///
+    /// Like [`TreeView::from_level_vec`](struct.TreeView.html#method.from_level_vec)
+    /// containers start out collapsed, while other items with children
+    /// become expanded containers.
+    pub fn from_levels<I: IntoIterator<Item = (usize, bool, T)>>(iter: I) -> Self {
+        Self {
+            nodes: TreeList::from_levels(iter).items,
+        }
+    }
+}
+
+/// A nested description of items for building a
+/// [`TreeView`](struct.TreeView.html) declaratively, see
+/// [`TreeView::from_builder`](struct.TreeView.html#method.from_builder).
+#[derive(Debug)]
+pub enum TreeBuilder<T> {
+    /// An item without any children.
+    Leaf(T),
+
+    /// A container item along with its children, which starts out expanded.
+    Node(T, Vec<TreeBuilder<T>>),
+}
+
 #[derive(Debug)]
 pub struct TreeList<T: Display + Debug> {
     items: Vec<TreeNode<T>>,
     height: usize,
+    next_id: usize,
+
+    /// Number of levels which may be visible at once
+    max_depth: Option<usize>,
+
+    /// Ids of the items which were collapsed to enforce the depth limit
+    depth_collapsed: HashSet<usize>,
+
+    /// Whether visibility is determined by the filter instead of the
+    /// collapsed state of the items
+    filter_active: bool,
+
+    /// Incremented whenever items are added, removed or borrowed mutably
+    revision: usize,
 }
 
 impl<T: Display + Debug> TreeList<T> {
@@ -85,7 +262,35 @@ impl<T: Display + Debug> TreeList<T> {
         Self {
             items: Vec::new(),
             height: 0,
+            next_id: 0,
+            max_depth: None,
+            depth_collapsed: HashSet::new(),
+            filter_active: false,
+            revision: 0,
+        }
+    }
+
+    /// Creates a new list from `(level, is_container, value)` triples in top
+    /// to bottom order.
+    ///
+    /// Levels deeper than one below the previous item are limited to that
+    /// depth.
+    pub fn from_levels<I: IntoIterator<Item = (usize, bool, T)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for (level, is_container, value) in iter {
+            let max_level = list.items.last().map(|item| item.level + 1).unwrap_or(0);
+            let mut node = list.node(value, is_container);
+            node.level = cmp::min(level, max_level);
+            list.items.push(node);
+        }
+        list.update_children();
+        list.update_heights();
+
+        // Items with children are always containers, like after an insertion
+        for item in &mut list.items {
+            item.is_container |= item.children > 0;
         }
+        list
     }
 
     pub fn len(&self) -> usize {
@@ -97,31 +302,69 @@ impl<T: Display + Debug> TreeList<T> {
     }
 
     pub fn height(&self) -> usize {
-        self.height
+        if self.filter_active {
+            self.items.iter().filter(|item| !item.is_filtered).count()
+        } else {
+            self.height
+        }
     }
 
     pub fn items(&self) -> &[TreeNode<T>] {
         &self.items
     }
 
+    /// Returns the item indices of all currently visible items in top to
+    /// bottom order, skipping items hidden within collapsed parents.
+    pub fn visible_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let first = if self.filter_active {
+            self.items.iter().position(|item| !item.is_filtered)
+        } else if self.items.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        iter::successors(first, move |&index| {
+            if self.filter_active {
+                return (index + 1..self.items.len()).find(|&i| !self.items[i].is_filtered);
+            }
+
+            let next = index + self.items[index].len();
+            if next < self.items.len() {
+                Some(next)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn get(&self, index: usize) -> Option<&T> {
         self.items.get(index).map(|item| &item.value)
     }
 
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.revision += 1;
         self.items.get_mut(index).map(|item| &mut item.value)
     }
 
     pub fn take_items(&mut self) -> Vec<T> {
+        self.revision += 1;
         self.height = 0;
         self.items.drain(0..).map(|item| item.value).collect()
     }
 
     pub fn clear(&mut self) {
+        self.revision += 1;
         self.items.clear();
         self.height = 0;
     }
 
+    /// Returns a counter which changes whenever items are added, removed or
+    /// might have been modified, i.e. whenever their widths need to be
+    /// measured again.
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
     /// Returns position on the x axis of the item at `index`
     ///
     /// `None` is returned when no item exists at `index`.
@@ -129,17 +372,46 @@ impl<T: Display + Debug> TreeList<T> {
         self.items.get(index).map(|item| item.offset())
     }
 
-    /// Returns width of the string representation of the item at `index`
+    /// Returns the level of the item at `index` within the tree
+    ///
+    /// `None` is returned when no item exists at `index`.
+    pub fn level(&self, index: usize) -> Option<usize> {
+        self.items.get(index).map(|item| item.level())
+    }
+
+    /// Returns whether the item at `index` is currently collapsed
     ///
     /// `None` is returned when no item exists at `index`.
-    pub fn width(&self, index: usize) -> Option<usize> {
-        self.items.get(index).map(|item| item.width())
+    pub fn is_collapsed(&self, index: usize) -> Option<bool> {
+        self.items.get(index).map(|item| item.is_collapsed)
     }
 
     pub fn insert_item(&mut self, placement: Placement, index: usize, value: T) -> Option<usize> {
         self.insert(placement, index, value, false)
     }
 
+    /// Inserts all `values` as a contiguous block of siblings, adjusting the
+    /// heights of their parents only once.
+    ///
+    /// `Placement::Parent` is not supported for multiple values and results
+    /// in nothing being inserted.
+    pub fn insert_items(
+        &mut self,
+        placement: Placement,
+        index: usize,
+        values: Vec<T>,
+    ) -> Option<usize> {
+        if values.is_empty() || (placement == Placement::Parent && values.len() > 1) {
+            return None;
+        }
+
+        let nodes = values
+            .into_iter()
+            .map(|value| self.node(value, false))
+            .collect();
+        self.insert_nodes(placement, index, nodes)
+    }
+
     pub fn insert_container_item(
         &mut self,
         placement: Placement,
@@ -152,13 +424,10 @@ impl<T: Display + Debug> TreeList<T> {
     pub fn remove(&mut self, index: usize) -> Option<T> {
         if index < self.len() {
             // Uncollapse to avoid additional height calculation
-            self.set_collapsed(index, false);
+            self.update_collapsed(index, false);
 
             // Reduce height and children of all parents
-            self.traverse_up(index, 0, |item| {
-                item.children -= 1;
-                item.height -= 1;
-            });
+            let inside_collapsed = self.shrink_parents(index, 0, 1, 1);
 
             // Remove item
             let removed_item = self.items.remove(index);
@@ -171,7 +440,9 @@ impl<T: Display + Debug> TreeList<T> {
             }
 
             // Reduce tree height
-            self.height -= 1;
+            if !inside_collapsed {
+                self.height -= 1;
+            }
 
             Some(removed_item.value)
         } else {
@@ -179,24 +450,61 @@ impl<T: Display + Debug> TreeList<T> {
         }
     }
 
+    /// Removes the item at `index` along with all of its children, keeping
+    /// their structure intact.
+    pub fn remove_subtree(&mut self, index: usize) -> Option<Vec<TreeNode<T>>> {
+        if index < self.len() {
+            Some(self.take_nodes(index))
+        } else {
+            None
+        }
+    }
+
+    /// Inserts a subtree previously removed via `remove_subtree`.
+    ///
+    /// `Placement::Parent` is only supported for subtrees consisting of a
+    /// single item and results in nothing being inserted otherwise.
+    pub fn insert_subtree(
+        &mut self,
+        placement: Placement,
+        index: usize,
+        nodes: Vec<TreeNode<T>>,
+    ) -> Option<usize> {
+        if nodes.is_empty() || (placement == Placement::Parent && nodes.len() > 1) {
+            return None;
+        }
+
+        // Subtrees may stem from other lists or be built from scratch, so
+        // their ids could match those of removed items, whose handles must
+        // never refer to another item
+        let mut nodes = nodes;
+        for node in &mut nodes {
+            node.id = self.next_id;
+            self.next_id += 1;
+        }
+        self.insert_nodes(placement, index, nodes)
+    }
+
     pub fn remove_children(&mut self, index: usize) -> Option<Vec<T>> {
         if index < self.len() {
-            let (item_height, item_children, was_collapsed) = {
-                let item = &self.items[index];
-                (item.height - 1, item.children, item.is_collapsed)
-            };
+            let was_collapsed = self.items[index].is_collapsed;
+            self.items[index].is_loaded = false;
 
             // Uncollapse to avoid additional height calculation
-            self.set_collapsed(index, false);
+            self.update_collapsed(index, false);
 
-            // Reduce height and children of all parents
-            self.traverse_up(index, 1, |item| {
-                item.children -= item_children;
-                item.height -= item_height;
-            });
+            let (item_height, item_children) = {
+                let item = &self.items[index];
+                (item.height - 1, item.children)
+            };
+
+            // Reduce height and children of the item and all of its parents
+            let inside_collapsed = self.shrink_parents(index, 1, item_children, item_height);
 
             // Reduce tree height
-            self.height -= item_height;
+            if !inside_collapsed {
+                self.height -= item_height;
+            }
 
             // Remove children
             let removed_items = if item_children > 0 {
@@ -219,7 +527,7 @@ impl<T: Display + Debug> TreeList<T> {
     pub fn remove_with_children(&mut self, index: usize) -> Option<Vec<T>> {
         if index < self.len() {
             // Uncollapse to avoid additional height calculation
-            self.set_collapsed(index, false);
+            self.update_collapsed(index, false);
 
             let (item_height, item_children) = {
                 let item = &self.items[index];
@@ -227,16 +535,15 @@ impl<T: Display + Debug> TreeList<T> {
             };
 
             // Reduce height and children of all parents
-            self.traverse_up(index, 0, |item| {
-                item.children -= item_children + 1;
-                item.height -= item_height;
-            });
+            let inside_collapsed = self.shrink_parents(index, 0, item_children + 1, item_height);
 
             // Remove item
             let item = self.items.remove(index);
 
             // Reduce tree height
-            self.height -= item.height;
+            if !inside_collapsed {
+                self.height -= item_height;
+            }
 
             // Remove children
             let mut removed_items = vec![item.value];
@@ -256,6 +563,269 @@ impl<T: Display + Debug> TreeList<T> {
         }
     }
 
+    /// Returns the indices of the immediate children of the item at `index`,
+    /// including those hidden by a collapsed state.
+    pub fn children_indices(&self, index: usize) -> Vec<usize> {
+        let mut indices = Vec::new();
+        if let Some(item) = self.items.get(index) {
+            let end = index + 1 + item.children;
+            let mut child = index + 1;
+            while child < end {
+                indices.push(child);
+                child += self.items[child].children + 1;
+            }
+        }
+        indices
+    }
+
+    /// Moves the item at `index` along with all of its children to the
+    /// specified position relative to the item at `target`.
+    ///
+    /// The collapsed state of all moved items is preserved. Moves into the
+    /// moved subtree itself and `Placement::Parent` are rejected.
+    ///
+    /// Returns the visual row of the moved item or `None` in case it is not
+    /// visible after the move or the move was rejected.
+    pub fn move_item(
+        &mut self,
+        index: usize,
+        placement: Placement,
+        target: usize,
+    ) -> Option<usize> {
+        let end = index + 1 + self.items.get(index)?.children;
+        if target >= self.len() || (index..end).contains(&target) || placement == Placement::Parent
+        {
+            return None;
+        }
+
+        let nodes = self.take_nodes(index);
+        let target = if target > index {
+            target - nodes.len()
+        } else {
+            target
+        };
+        self.insert_nodes(placement, target, nodes)
+    }
+
+    /// Swaps the item at `index` along with all of its children with its
+    /// previous sibling, returning the new index of the item.
+    ///
+    /// The item stays in place in case it already is the first sibling.
+    pub fn move_up(&mut self, index: usize) -> Option<usize> {
+        let end = index + 1 + self.items.get(index)?.children;
+        match self.previous_sibling(index) {
+            Some(previous) => {
+                // Levels, children and heights of all items stay the same,
+                // the two subtrees merely trade places
+                self.items[previous..end].rotate_left(index - previous);
+                Some(previous)
+            }
+            None => Some(index),
+        }
+    }
+
+    /// Swaps the item at `index` along with all of its children with its
+    /// next sibling, returning the new index of the item.
+    ///
+    /// The item stays in place in case it already is the last sibling.
+    pub fn move_down(&mut self, index: usize) -> Option<usize> {
+        let next = index + 1 + self.items.get(index)?.children;
+        if next < self.len() && self.items[next].level == self.items[index].level {
+            self.move_up(next)?;
+            Some(index + 1 + self.items[index].children)
+        } else {
+            Some(index)
+        }
+    }
+
+    /// Removes all items for which `f` returns `false`, handling their
+    /// children according to `policy`.
+    ///
+    /// Children and heights are recomputed once all items have been visited.
+    pub fn retain<F: Fn(&T) -> bool>(&mut self, f: F, policy: RetainPolicy) {
+        self.revision += 1;
+
+        // Levels of the removed items which are parents of the current item
+        let mut removed: Vec<usize> = Vec::new();
+        let mut items = Vec::with_capacity(self.items.len());
+        for mut item in self.items.drain(..) {
+            while removed.last().is_some_and(|&level| level >= item.level) {
+                removed.pop();
+            }
+
+            if policy == RetainPolicy::RemoveChildren && !removed.is_empty() {
+                continue;
+            }
+
+            if f(&item.value) {
+                item.level -= removed.len();
+                items.push(item);
+            } else {
+                removed.push(item.level);
+            }
+        }
+        self.items = items;
+        self.update_children();
+        self.update_heights();
+    }
+
+    /// Sorts the immediate children of the item at `index` by `cmp`, moving
+    /// each child along with all of its own children.
+    ///
+    /// The sort is stable and leaves all heights and collapsed states intact.
+    pub fn sort_children_by<F>(&mut self, index: usize, cmp: &F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let end = match self.items.get(index) {
+            Some(item) => index + 1 + item.children,
+            None => return,
+        };
+
+        let mut blocks: Vec<Vec<TreeNode<T>>> = Vec::new();
+        let mut nodes = self.items.drain(index + 1..end).peekable();
+        while let Some(child) = nodes.next() {
+            let mut block = vec![child];
+            while nodes.peek().is_some_and(|node| node.level > block[0].level) {
+                block.extend(nodes.next());
+            }
+            blocks.push(block);
+        }
+        drop(nodes);
+
+        blocks.sort_by(|a, b| cmp(&a[0].value, &b[0].value));
+        self.items
+            .splice(index + 1..index + 1, blocks.into_iter().flatten());
+    }
+
+    /// Sorts the children of the item at `index` and of all of its
+    /// descendants by `cmp`.
+    pub fn sort_subtree_by<F>(&mut self, index: usize, cmp: &F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        if let Some(item) = self.items.get(index) {
+            // Going backwards only ever reorders items within the subtree of
+            // the item currently visited, so all indices yet to be visited
+            // stay valid
+            for i in (index..=index + item.children).rev() {
+                self.sort_children_by(i, cmp);
+            }
+        }
+    }
+
+    /// Returns the index of the previous sibling of the item at `index`.
+    pub fn previous_sibling(&self, index: usize) -> Option<usize> {
+        self.items.get(index)?;
+        let siblings = self.sibling_indices(index);
+        let position = siblings.iter().position(|&i| i == index)?;
+        position.checked_sub(1).map(|position| siblings[position])
+    }
+
+    /// Returns the index of the sibling following the item at `index`.
+    pub fn next_sibling(&self, index: usize) -> Option<usize> {
+        let item = self.items.get(index)?;
+        let next = index + 1 + item.children;
+        self.items
+            .get(next)
+            .filter(|next| next.level == item.level)
+            .map(|_| next)
+    }
+
+    /// Returns the indices of all siblings of the item at `index`, including
+    /// the item itself.
+    pub fn sibling_indices(&self, index: usize) -> Vec<usize> {
+        match self.item_parent_index(index) {
+            Some(parent) => self.children_indices(parent),
+            None => self.root_indices(),
+        }
+    }
+
+    /// Returns the indices of all top level items.
+    pub fn root_indices(&self) -> Vec<usize> {
+        if self.items.is_empty() {
+            return Vec::new();
+        }
+        iter::successors(Some(0), |&i| {
+            Some(i + 1 + self.items[i].children).filter(|&next| next < self.len())
+        })
+        .collect()
+    }
+
+    /// Returns the index of the item at the given structural `path`, where
+    /// each entry is the position of an item among its siblings, starting
+    /// with the top level items.
+    pub fn path_to_item_index(&self, path: &[usize]) -> Option<usize> {
+        let (&first, rest) = path.split_first()?;
+        let mut index = *self.root_indices().get(first)?;
+        for &n in rest {
+            index = *self.children_indices(index).get(n)?;
+        }
+        Some(index)
+    }
+
+    /// Toggles whether the item at `index` is part of the selection.
+    pub fn toggle_selected(&mut self, index: usize) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.is_selected = !item.is_selected;
+        }
+    }
+
+    /// Checks or unchecks the item at `index` along with all of its
+    /// descendants.
+    pub fn set_checked(&mut self, index: usize, checked: bool) {
+        if let Some(children) = self.items.get(index).map(|item| item.children) {
+            for item in &mut self.items[index..=index + children] {
+                item.is_checked = checked;
+            }
+        }
+    }
+
+    /// Returns the checkbox state of the item at `index`.
+    ///
+    /// The state of items with children is derived from their descendants
+    /// without any children of their own.
+    pub fn check_state(&self, index: usize) -> Option<CheckState> {
+        let item = self.items.get(index)?;
+        if item.children == 0 {
+            return Some(if item.is_checked {
+                CheckState::Checked
+            } else {
+                CheckState::Unchecked
+            });
+        }
+
+        let (checked, total) = self.items[index + 1..=index + item.children]
+            .iter()
+            .filter(|item| item.children == 0)
+            .fold((0, 0), |(checked, total), item| {
+                (checked + item.is_checked as usize, total + 1)
+            });
+
+        Some(if checked == 0 {
+            CheckState::Unchecked
+        } else if checked == total {
+            CheckState::Checked
+        } else {
+            CheckState::Partial
+        })
+    }
+
+    /// Enables or disables the item at `index`.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.is_enabled = enabled;
+        }
+    }
+
+    /// Returns whether the item at `index` exists and is enabled.
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.items
+            .get(index)
+            .map(|item| item.is_enabled)
+            .unwrap_or(false)
+    }
+
     // TODO rename and cleanup the methods below
     pub fn is_container_item(&self, index: usize) -> bool {
         self.items
@@ -264,6 +834,21 @@ impl<T: Display + Debug> TreeList<T> {
             .unwrap_or(false)
     }
 
+    /// Returns whether the children of the item at `index` were requested
+    /// from a lazy loader since it was inserted or its children were removed.
+    pub fn is_loaded(&self, index: usize) -> bool {
+        self.items
+            .get(index)
+            .map(|item| item.is_loaded)
+            .unwrap_or(false)
+    }
+
+    pub fn set_loaded(&mut self, index: usize, loaded: bool) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.is_loaded = loaded;
+        }
+    }
+
     pub fn get_children(&self, index: usize) -> usize {
         self.items.get(index).map(|item| item.children).unwrap_or(0)
     }
@@ -276,6 +861,18 @@ impl<T: Display + Debug> TreeList<T> {
     }
 
     pub fn set_collapsed(&mut self, index: usize, collapsed: bool) {
+        if !collapsed && self.exceeds_max_depth(index) {
+            return;
+        }
+        self.update_collapsed(index, collapsed);
+    }
+
+    /// Collapses or expands the item at `index` regardless of the depth
+    /// limit, propagating the change in height to its parents.
+    ///
+    /// Removals rely on this to temporarily expand items, as the heights of
+    /// their parents would end up stale otherwise.
+    fn update_collapsed(&mut self, index: usize, collapsed: bool) {
         if index < self.len() {
             let offset = {
                 let item = &mut self.items[index];
@@ -303,19 +900,21 @@ impl<T: Display + Debug> TreeList<T> {
             if let Some(offset) = offset {
                 let mut inside_collapsed = false;
                 self.traverse_up(index, 1, |item| {
-                    inside_collapsed |= item.is_collapsed;
+                    // Ignore all parents beyond the first collapsed one as the
+                    // changes in height cannot propagate any further
+                    if inside_collapsed {
+                        return;
+                    }
 
                     // Modify the collapsed height of the parent if required
                     if item.is_collapsed {
+                        inside_collapsed = true;
                         if collapsed {
                             item.collapsed_height = Some(item.collapsed_height.unwrap() - offset);
                         } else {
                             item.collapsed_height = Some(item.collapsed_height.unwrap() + offset);
                         }
-
-                    // Ignore all parents beyond the first collapsed one as the
-                    // changes in height cannot visibly propagate any further
-                    } else if !inside_collapsed {
+                    } else {
                         if collapsed {
                             item.height -= offset;
                         } else {
@@ -344,26 +943,262 @@ impl<T: Display + Debug> TreeList<T> {
         }
     }
 
-    pub fn row_to_item_index(&self, row: usize) -> usize {
-        let mut i = 0;
-        let mut item_index = row;
+    /// Collapses or expands all container items at once.
+    ///
+    /// Heights are recomputed in a single pass afterwards instead of being
+    /// propagated for each individual item.
+    pub fn set_all_collapsed(&mut self, collapsed: bool) {
+        self.set_range_collapsed(0..self.len(), collapsed);
+    }
+
+    /// Collapses or expands the item at `index` along with all containers
+    /// within its subtree.
+    pub fn set_subtree_collapsed(&mut self, index: usize, collapsed: bool) {
+        if let Some(item) = self.items.get(index) {
+            let end = index + 1 + item.children;
+            self.set_range_collapsed(index..end, collapsed);
+        }
+    }
 
-        while i < self.items.len() {
-            if item_index == i {
-                return i;
-            } else if self.get_collapsed(i) {
-                let children = self.get_children(i);
-                i += children;
-                item_index += children;
+    /// Returns whether the item at `index` and all containers within its
+    /// subtree are expanded, apart from those collapsed due to the depth
+    /// limit.
+    pub fn is_subtree_expanded(&self, index: usize) -> bool {
+        match self.items.get(index) {
+            Some(item) => self.items[index..=index + item.children]
+                .iter()
+                .all(|item| {
+                    !item.is_container
+                        || !item.is_collapsed
+                        || self.depth_collapsed.contains(&item.id)
+                }),
+            None => false,
+        }
+    }
+
+    /// Collapses or expands all containers within `range`, which must cover
+    /// complete subtrees, and recomputes the heights of all items afterwards.
+    fn set_range_collapsed(&mut self, range: Range<usize>, collapsed: bool) {
+        for item in &mut self.items[range.clone()] {
+            if item.is_container {
+                item.is_collapsed = collapsed;
             }
+            self.depth_collapsed.remove(&item.id);
+        }
 
-            i += 1;
+        if let (false, Some(depth)) = (collapsed, self.max_depth) {
+            for item in &mut self.items[range] {
+                if item.level + 1 >= depth && item.children > 0 {
+                    item.is_collapsed = true;
+                    self.depth_collapsed.insert(item.id);
+                }
+            }
         }
+        self.update_heights();
+    }
 
-        self.len()
+    /// Limits the number of levels which may be visible at once, collapsing
+    /// all items on the deepest visible level.
+    ///
+    /// Items collapsed due to a previous limit are expanded again once they
+    /// are within the new limit. At least the top level always stays visible.
+    pub fn set_max_depth(&mut self, depth: Option<usize>) {
+        self.max_depth = depth.map(|depth| cmp::max(depth, 1));
+
+        let ids = mem::take(&mut self.depth_collapsed);
+        for index in 0..self.len() {
+            if ids.contains(&self.items[index].id) {
+                if self.exceeds_max_depth(index) {
+                    self.depth_collapsed.insert(self.items[index].id);
+                } else {
+                    self.set_collapsed(index, false);
+                }
+            }
+        }
+        self.apply_max_depth();
+    }
+
+    /// Shows only the items for which `matcher` returns `true`, along with
+    /// all of their ancestors, regardless of their collapsed state.
+    ///
+    /// The collapsed state of the items is kept as is and takes effect again
+    /// once the filter is removed by passing `None`. Items inserted while the
+    /// filter is active are visible until it is applied again.
+    pub fn set_filter(&mut self, matcher: Option<&dyn Fn(&T) -> bool>) {
+        let matcher = match matcher {
+            Some(matcher) => matcher,
+            None => {
+                self.filter_active = false;
+                return;
+            }
+        };
+
+        let mut ancestors: Vec<usize> = Vec::new();
+        for index in 0..self.len() {
+            ancestors.truncate(self.items[index].level);
+
+            let is_match = matcher(&self.items[index].value);
+            self.items[index].is_filtered = !is_match;
+
+            // Reveal all ancestors which are still filtered, stopping at the
+            // first visible one as its own ancestors are visible already
+            if is_match {
+                for &ancestor in ancestors.iter().rev() {
+                    if !self.items[ancestor].is_filtered {
+                        break;
+                    }
+                    self.items[ancestor].is_filtered = false;
+                }
+            }
+            ancestors.push(index);
+        }
+        self.filter_active = true;
+    }
+
+    /// Returns whether a filter is currently applied.
+    pub fn is_filtered(&self) -> bool {
+        self.filter_active
+    }
+
+    /// Collapses all expanded items with children on the deepest visible
+    /// level, e.g. after new children were inserted or items were moved
+    /// onto that level.
+    pub fn apply_max_depth(&mut self) {
+        if self.max_depth.is_none() {
+            return;
+        }
+
+        for index in 0..self.len() {
+            let item = &self.items[index];
+            if self.exceeds_max_depth(index) && item.children > 0 && !item.is_collapsed {
+                self.depth_collapsed.insert(item.id);
+                self.set_collapsed(index, true);
+            }
+        }
+    }
+
+    /// Returns whether the children of the item at `index` are beyond the
+    /// depth limit.
+    pub fn exceeds_max_depth(&self, index: usize) -> bool {
+        match (self.max_depth, self.items.get(index)) {
+            (Some(depth), Some(item)) => item.level + 1 >= depth,
+            _ => false,
+        }
+    }
+
+    /// Expands all collapsed ancestors of the item at `index` so that it
+    /// becomes visible, returning its visual row afterwards.
+    ///
+    /// Returns `None` if no item exists at `index` or it is beyond the depth
+    /// limit.
+    pub fn expand_to(&mut self, index: usize) -> Option<usize> {
+        let level = self.items.get(index)?.level;
+        if self.max_depth.is_some_and(|depth| level >= depth) {
+            return None;
+        }
+
+        let mut ancestors = Vec::new();
+        let mut current = index;
+        while let Some(parent) = self.item_parent_index(current) {
+            ancestors.push(parent);
+            current = parent;
+        }
+
+        // Expand from the outermost ancestor inwards so that each expansion
+        // propagates its height to already visible parents
+        for parent in ancestors.into_iter().rev() {
+            self.set_collapsed(parent, false);
+        }
+
+        Some(self.item_index_to_row(index))
+    }
+
+    /// Returns the index of the item with the given `id`.
+    ///
+    /// `None` is returned once the item has been removed from the list.
+    pub fn id_to_item_index(&self, id: usize) -> Option<usize> {
+        self.items.iter().position(|item| item.id == id)
+    }
+
+    /// Returns whether the item at `index` is not hidden by any collapsed
+    /// parent.
+    pub fn is_visible(&self, index: usize) -> bool {
+        if self.filter_active {
+            return self.items.get(index).is_some_and(|item| !item.is_filtered);
+        }
+
+        let mut current = index;
+        while let Some(parent) = self.item_parent_index(current) {
+            if self.items[parent].is_collapsed {
+                return false;
+            }
+            current = parent;
+        }
+        index < self.len()
+    }
+
+    /// Returns the index of the item which visually represents the item at
+    /// `index`, i.e. its outermost collapsed parent or the item itself in
+    /// case it is visible.
+    pub fn visible_ancestor(&self, index: usize) -> usize {
+        if self.filter_active {
+            let mut current = index;
+            while !self.is_visible(current) {
+                match self.item_parent_index(current) {
+                    Some(parent) => current = parent,
+                    None => return index,
+                }
+            }
+            return current;
+        }
+
+        let mut visible = index;
+        let mut current = index;
+        while let Some(parent) = self.item_parent_index(current) {
+            if self.items[parent].is_collapsed {
+                visible = parent;
+            }
+            current = parent;
+        }
+        visible
+    }
+
+    /// Returns the id which will be assigned to the next inserted item.
+    pub fn next_id(&self) -> usize {
+        self.next_id
+    }
+
+    pub fn row_to_item_index(&self, row: usize) -> usize {
+        if self.filter_active {
+            return self.visible_indices().nth(row).unwrap_or(self.len());
+        }
+
+        let mut i = 0;
+        let mut item_index = row;
+
+        while i < self.items.len() {
+            if item_index == i {
+                return i;
+            } else if self.get_collapsed(i) {
+                let children = self.get_children(i);
+                i += children;
+                item_index += children;
+            }
+
+            i += 1;
+        }
+
+        self.len()
     }
 
     pub fn item_index_to_row(&self, index: usize) -> usize {
+        if self.filter_active {
+            return self.items[..cmp::min(index, self.len())]
+                .iter()
+                .filter(|item| !item.is_filtered)
+                .count();
+        }
+
         let mut i = 0;
         let mut row = index;
 
@@ -382,9 +1217,10 @@ impl<T: Display + Debug> TreeList<T> {
 
     /// Returns index of the parent of the node at the input index.
     ///
-    /// `None` is returned if the node at index is a root (has no ancestors).
+    /// `None` is returned if the node at index is a root (has no ancestors)
+    /// or if no node exists at `index`.
     pub fn item_parent_index(&self, index: usize) -> Option<usize> {
-        let level = self.items[index].level;
+        let level = self.items.get(index)?.level;
         for i in 0..=index {
             if self.items[index - i].level < level {
                 return Some(index - i);
@@ -402,10 +1238,111 @@ impl<T: Display + Debug> TreeList<T> {
         value: T,
         is_container: bool,
     ) -> Option<usize> {
+        let node = self.node(value, is_container);
+        self.insert_nodes(placement, index, vec![node])
+    }
+
+    /// Creates a new node with a unique id.
+    fn node(&mut self, value: T, is_container: bool) -> TreeNode<T> {
+        let id = self.next_id;
+        self.next_id += 1;
+        TreeNode::new(id, value, is_container)
+    }
+
+    /// Inserts a contiguous block of nodes at the given `index`.
+    ///
+    /// The levels of the nodes are relative to the level at which the block
+    /// is inserted and their heights must already be consistent within the
+    /// block. `Placement::Parent` is only supported for a single node.
+    fn insert_nodes(
+        &mut self,
+        placement: Placement,
+        index: usize,
+        mut nodes: Vec<TreeNode<T>>,
+    ) -> Option<usize> {
+        debug_assert!(placement != Placement::Parent || nodes.len() == 1);
+        self.revision += 1;
+
         // Limit index to the maximum index of the items vec
         let index = cmp::min(index, cmp::max(self.len() as isize - 1, 0) as usize);
 
-        let (parent_index, item_index, level, move_children) = if self.items.is_empty() {
+        let (parent_index, item_index, level, move_children) =
+            self.insertion_point(placement, index);
+
+        // Visible height of the block as seen by its parents
+        let count = nodes.len();
+        let mut height = 0;
+        let mut i = 0;
+        while i < count {
+            height += nodes[i].height;
+            i += nodes[i].children + 1;
+        }
+
+        let mut inside_collapsed = false;
+        if let Some(parent_index) = parent_index {
+            self.traverse_up(parent_index, 1, |item| {
+                if item.level < level {
+                    // Automatically convert the item into a container
+                    item.is_container = true;
+                    item.children += count;
+
+                    // In case the parent is collapsed we increment the stored
+                    // collapsed height instead of the actual one and exit early
+                    // to avoid messing up any parents further up the in the tree
+                    if !inside_collapsed {
+                        if item.is_collapsed {
+                            inside_collapsed = true;
+                            item.collapsed_height = Some(item.collapsed_height.unwrap() + height);
+                        } else {
+                            item.height += height;
+                        }
+                    }
+                }
+            });
+        }
+
+        // Move children to a deeper level
+        if move_children {
+            let children_height = self.items[item_index].height;
+            let children = self.traverse_down(item_index, false, |item| {
+                item.level += 1;
+            });
+
+            let node = &mut nodes[0];
+            node.children = children;
+            node.height = 1 + children_height;
+            node.is_container = true;
+            node.is_collapsed = false;
+            node.collapsed_height = None;
+        }
+
+        for node in &mut nodes {
+            node.level += level;
+        }
+        self.items.splice(item_index..item_index, nodes);
+
+        // Only increment the tree height if the item was not inserted within a
+        // already collapsed parent
+        if !inside_collapsed {
+            self.height += height;
+
+            // We only return the visual row index in case the inserted item is
+            // visible
+            Some(self.item_index_to_row(item_index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of the parent, the index at which to insert, the
+    /// level of the inserted item and whether the item should adopt the
+    /// item at `index` as its child.
+    fn insertion_point(
+        &self,
+        placement: Placement,
+        index: usize,
+    ) -> (Option<usize>, usize, usize, bool) {
+        if self.items.is_empty() {
             (None, 0, 0, false)
         } else {
             match placement {
@@ -453,6 +1390,16 @@ impl<T: Display + Debug> TreeList<T> {
                         false,
                     )
                 }
+                Placement::NthChild(n) => {
+                    let parent = self.items.get(index).expect("Tree should not be empty");
+                    let item_index = self
+                        .children_indices(index)
+                        .get(n)
+                        .cloned()
+                        .unwrap_or(index + 1 + parent.children);
+
+                    (Some(index), item_index, parent.level + 1, false)
+                }
                 Placement::Parent => {
                     // Get level of first child that we replace
                     let level = {
@@ -472,65 +1419,117 @@ impl<T: Display + Debug> TreeList<T> {
                     )
                 }
             }
+        }
+    }
+
+    /// Removes the item at `index` along with all of its children, keeping
+    /// their structure and collapsed state intact.
+    ///
+    /// The levels of the returned nodes are relative to the removed item.
+    fn take_nodes(&mut self, index: usize) -> Vec<TreeNode<T>> {
+        let (level, item_height, item_children) = {
+            let item = &self.items[index];
+            (item.level, item.height, item.children)
         };
 
-        let mut inside_collapsed = false;
-        if let Some(parent_index) = parent_index {
-            self.traverse_up(parent_index, 1, |item| {
-                if item.level < level {
-                    // Automatically convert the item into a container
-                    item.is_container = true;
-                    item.children += 1;
+        // Reduce height and children of all parents
+        let inside_collapsed = self.shrink_parents(index, 0, item_children + 1, item_height);
 
-                    // In case the parent is collapsed we increment the stored
-                    // collapsed height instead of the actual one and exit early
-                    // to avoid messing up any parents further up the in the tree
-                    if !inside_collapsed {
-                        if item.is_collapsed {
-                            inside_collapsed = true;
-                            item.collapsed_height = Some(item.collapsed_height.unwrap() + 1);
-                        } else {
-                            item.height += 1;
-                        }
-                    }
-                }
-            });
+        // Reduce tree height
+        if !inside_collapsed {
+            self.height -= item_height;
         }
 
-        // Move children to a deeper level
-        let children = if move_children {
-            self.traverse_down(item_index, false, |item| {
-                item.level += 1;
+        self.items
+            .drain(index..index + 1 + item_children)
+            .map(|mut item| {
+                item.level -= level;
+                item
             })
-        } else {
-            0
-        };
+            .collect()
+    }
+
+    /// Recomputes the number of children of all items from their levels.
+    fn update_children(&mut self) {
+        // Indices of all parents of the current item
+        let mut parents: Vec<usize> = Vec::new();
+        for index in 0..self.items.len() {
+            let level = self.items[index].level;
+            while parents
+                .last()
+                .is_some_and(|&parent| self.items[parent].level >= level)
+            {
+                parents.pop();
+            }
 
-        let initially_collapsed = is_container && children == 0;
-        self.items.insert(
-            item_index,
-            TreeNode {
-                value,
-                is_collapsed: initially_collapsed,
-                level,
-                children,
-                height: 1 + children,
-                is_container,
-                collapsed_height: if initially_collapsed { Some(1) } else { None },
-            },
-        );
+            for &parent in &parents {
+                self.items[parent].children += 1;
+            }
+            self.items[index].children = 0;
+            parents.push(index);
+        }
+    }
 
-        // Only increment the tree height if the item was not inserted within a
-        // already collapsed parent
-        if !inside_collapsed {
-            self.height += 1;
+    /// Recomputes the heights of all items and of the tree itself from their
+    /// collapsed state, going from the bottom of the tree to the top so the
+    /// heights of all children are known before their parent is visited.
+    fn update_heights(&mut self) {
+        for index in (0..self.items.len()).rev() {
+            let end = index + 1 + self.items[index].children;
+            let mut height = 1;
+            let mut child = index + 1;
+            while child < end {
+                height += self.items[child].height;
+                child += self.items[child].children + 1;
+            }
 
-            // We only return the visual row index in case the inserted item is
-            // visible
-            Some(self.item_index_to_row(item_index))
-        } else {
-            None
+            let item = &mut self.items[index];
+            if item.is_collapsed {
+                item.height = 1;
+                item.collapsed_height = Some(height);
+            } else {
+                item.height = height;
+                item.collapsed_height = None;
+            }
+        }
+
+        let mut height = 0;
+        let mut index = 0;
+        while index < self.items.len() {
+            height += self.items[index].height;
+            index += self.items[index].children + 1;
         }
+        self.height = height;
+    }
+
+    /// Reduces the number of children and the height of all parents of the
+    /// item at `index`, including the item itself in case `offset` is `1`.
+    ///
+    /// In case a collapsed parent is encountered, its stored collapsed height
+    /// is reduced instead and the heights of all parents further up the tree
+    /// are left untouched. Returns whether such a collapsed parent was found.
+    fn shrink_parents(
+        &mut self,
+        index: usize,
+        offset: usize,
+        children: usize,
+        height: usize,
+    ) -> bool {
+        self.revision += 1;
+        let mut inside_collapsed = false;
+        self.traverse_up(index, offset, |item| {
+            item.children -= children;
+
+            if !inside_collapsed {
+                if item.is_collapsed {
+                    inside_collapsed = true;
+                    item.collapsed_height = Some(item.collapsed_height.unwrap() - height);
+                } else {
+                    item.height -= height;
+                }
+            }
+        });
+        inside_collapsed
     }
 
     fn traverse_up<C: FnMut(&mut TreeNode<T>)>(&mut self, index: usize, offset: usize, mut cb: C) {
@@ -627,6 +1626,49 @@ mod test {
 
             list
         }
+
+        /// Recomputes the number of children and the heights of all items
+        /// from scratch and asserts that they match the stored ones.
+        fn validate(&self) {
+            for (index, item) in self.items.iter().enumerate() {
+                let children = self.items[index + 1..]
+                    .iter()
+                    .take_while(|child| child.level > item.level)
+                    .count();
+                assert_eq!(item.children, children, "children of {}", index);
+            }
+
+            for (index, item) in self.items.iter().enumerate() {
+                let end = index + 1 + item.children;
+                let mut height = 1;
+                let mut child = index + 1;
+                while child < end {
+                    height += self.items[child].height;
+                    child += self.items[child].children + 1;
+                }
+
+                if item.is_collapsed {
+                    assert_eq!(item.height, 1, "height of {}", index);
+                    assert_eq!(
+                        item.collapsed_height,
+                        Some(height),
+                        "collapsed height of {}",
+                        index
+                    );
+                } else {
+                    assert_eq!(item.height, height, "height of {}", index);
+                    assert_eq!(item.collapsed_height, None, "collapsed height of {}", index);
+                }
+            }
+
+            let roots: usize = self
+                .root_indices()
+                .into_iter()
+                .map(|index| self.items[index].height)
+                .sum();
+            assert_eq!(self.height, roots);
+            assert_eq!(self.height, self.visible_indices().count());
+        }
     }
 
     #[test]
@@ -2014,4 +3056,866 @@ mod test {
 
         assert_eq!(tree.remove(0).unwrap(), TreeItem { value: 42 });
     }
+
+    #[test]
+    fn test_visible_indices() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        assert_eq!(tree.visible_indices().count(), 0);
+
+        tree.insert_item(Placement::LastChild, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 0, "4".to_string());
+        tree.insert_item(Placement::After, 0, "5".to_string());
+
+        assert_eq!(
+            tree.visible_indices().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+
+        tree.set_collapsed(1, true);
+        assert_eq!(tree.visible_indices().collect::<Vec<_>>(), vec![0, 1, 3, 4]);
+
+        tree.set_collapsed(0, true);
+        assert_eq!(tree.visible_indices().collect::<Vec<_>>(), vec![0, 4]);
+        assert_eq!(tree.visible_indices().count(), tree.height());
+    }
+
+    #[test]
+    fn test_set_all_collapsed() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::LastChild, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 2, "4".to_string());
+        tree.insert_item(Placement::LastChild, 0, "5".to_string());
+        tree.insert_item(Placement::After, 0, "6".to_string());
+        tree.insert_container_item(Placement::After, 5, "7".to_string());
+
+        tree.set_collapsed(2, true);
+        tree.set_all_collapsed(true);
+
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, true, "1".to_string(), 4, 1),
+                (0, false, "6".to_string(), 0, 1),
+                (0, true, "7".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.height(), 3);
+
+        tree.set_collapsed(0, false);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 4, 3),
+                (1, true, "2".to_string(), 2, 1),
+                (1, false, "5".to_string(), 0, 1),
+                (0, false, "6".to_string(), 0, 1),
+                (0, true, "7".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.height(), 5);
+
+        tree.set_all_collapsed(false);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 4, 5),
+                (1, false, "2".to_string(), 2, 3),
+                (2, false, "3".to_string(), 1, 2),
+                (3, false, "4".to_string(), 0, 1),
+                (1, false, "5".to_string(), 0, 1),
+                (0, false, "6".to_string(), 0, 1),
+                (0, false, "7".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.height(), 7);
+    }
+
+    #[test]
+    fn test_item_parent_index() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::LastChild, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 0, "4".to_string());
+        tree.insert_item(Placement::After, 0, "5".to_string());
+
+        assert_eq!(tree.item_parent_index(0), None);
+        assert_eq!(tree.item_parent_index(1), Some(0));
+        assert_eq!(tree.item_parent_index(2), Some(1));
+        assert_eq!(tree.item_parent_index(3), Some(0));
+        assert_eq!(tree.item_parent_index(4), None);
+        assert_eq!(tree.item_parent_index(5), None);
+    }
+
+    #[test]
+    fn test_children_indices() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::LastChild, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 2, "4".to_string());
+        tree.insert_item(Placement::LastChild, 0, "5".to_string());
+        tree.insert_item(Placement::After, 0, "6".to_string());
+
+        assert_eq!(tree.children_indices(0), vec![1, 4]);
+        assert_eq!(tree.children_indices(1), vec![2]);
+        assert_eq!(tree.children_indices(4), Vec::<usize>::new());
+        assert_eq!(tree.children_indices(6), Vec::<usize>::new());
+
+        tree.set_collapsed(0, true);
+        assert_eq!(tree.children_indices(0), vec![1, 4]);
+    }
+
+    #[test]
+    fn test_remove_and_reinsert_children_when_collapsed() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::LastChild, 0, "A".to_string());
+        tree.insert_item(Placement::LastChild, 0, "B".to_string());
+        tree.insert_item(Placement::LastChild, 1, "C".to_string());
+        tree.insert_item(Placement::LastChild, 2, "D".to_string());
+        tree.insert_item(Placement::LastChild, 2, "E".to_string());
+
+        tree.set_collapsed(2, true);
+        tree.set_collapsed(1, true);
+
+        assert_eq!(
+            tree.remove_children(2),
+            Some(vec!["D".to_string(), "E".to_string()])
+        );
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "A".to_string(), 2, 2),
+                (1, true, "B".to_string(), 1, 1)
+            ]
+        );
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.height(), 2);
+
+        assert_eq!(
+            tree.insert_item(Placement::After, 0, "F".to_string()),
+            Some(2)
+        );
+        assert_eq!(
+            tree.insert_item(Placement::LastChild, 2, "D".to_string()),
+            None
+        );
+        assert_eq!(tree.remove(2), Some("C".to_string()));
+        assert_eq!(
+            tree.insert_item(Placement::LastChild, 1, "C".to_string()),
+            None
+        );
+        assert_eq!(
+            tree.insert_item(Placement::LastChild, 3, "E".to_string()),
+            None
+        );
+
+        tree.set_collapsed(1, false);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "A".to_string(), 4, 5),
+                (1, false, "B".to_string(), 3, 4),
+                (2, false, "D".to_string(), 0, 1),
+                (2, false, "C".to_string(), 1, 2),
+                (3, false, "E".to_string(), 0, 1),
+                (0, false, "F".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.height(), 6);
+
+        tree.set_collapsed(3, true);
+        tree.set_collapsed(1, true);
+        assert_eq!(
+            tree.remove_with_children(3),
+            Some(vec!["C".to_string(), "E".to_string()])
+        );
+
+        tree.set_collapsed(1, false);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "A".to_string(), 2, 3),
+                (1, false, "B".to_string(), 1, 2),
+                (2, false, "D".to_string(), 0, 1),
+                (0, false, "F".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.height(), 4);
+    }
+
+    #[test]
+    fn test_move_item() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::LastChild, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 2, "4".to_string());
+        tree.insert_container_item(Placement::LastChild, 1, "5".to_string());
+        tree.insert_item(Placement::After, 0, "6".to_string());
+        tree.insert_item(Placement::LastChild, 5, "7".to_string());
+
+        tree.set_collapsed(2, true);
+
+        // Moves into the subtree itself are rejected
+        assert_eq!(tree.move_item(1, Placement::LastChild, 2), None);
+        assert_eq!(tree.move_item(1, Placement::After, 1), None);
+        assert_eq!(tree.move_item(1, Placement::Parent, 5), None);
+        assert_eq!(tree.move_item(1, Placement::LastChild, 10), None);
+
+        assert_eq!(tree.move_item(1, Placement::LastChild, 5), Some(3));
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 0, 1),
+                (0, false, "6".to_string(), 5, 5),
+                (1, false, "7".to_string(), 0, 1),
+                (1, false, "2".to_string(), 3, 3),
+                (2, true, "3".to_string(), 1, 1),
+                (2, true, "5".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.height(), 6);
+
+        tree.set_collapsed(4, false);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 0, 1),
+                (0, false, "6".to_string(), 5, 6),
+                (1, false, "7".to_string(), 0, 1),
+                (1, false, "2".to_string(), 3, 4),
+                (2, false, "3".to_string(), 1, 2),
+                (3, false, "4".to_string(), 0, 1),
+                (2, true, "5".to_string(), 0, 1)
+            ]
+        );
+
+        // Moving into a collapsed parent hides the item
+        tree.set_collapsed(1, true);
+        assert_eq!(tree.move_item(0, Placement::FirstChild, 3), None);
+        assert_eq!(tree.to_vec(), vec![(0, true, "6".to_string(), 6, 1)]);
+        assert_eq!(tree.height(), 1);
+
+        tree.set_collapsed(0, false);
+        assert_eq!(tree.move_item(2, Placement::Before, 0), Some(0));
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "2".to_string(), 4, 5),
+                (1, false, "1".to_string(), 0, 1),
+                (1, false, "3".to_string(), 1, 2),
+                (2, false, "4".to_string(), 0, 1),
+                (1, true, "5".to_string(), 0, 1),
+                (0, false, "6".to_string(), 1, 2),
+                (1, false, "7".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.height(), 7);
+    }
+
+    #[test]
+    fn test_insert_parent_collapsed_child() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::LastChild, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.set_collapsed(0, true);
+
+        assert_eq!(
+            tree.insert_item(Placement::Parent, 0, "Parent".to_string()),
+            Some(0)
+        );
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "Parent".to_string(), 2, 2),
+                (1, true, "1".to_string(), 1, 1)
+            ]
+        );
+        assert_eq!(tree.height(), 2);
+    }
+
+    #[test]
+    fn test_insert_items() {
+        use super::{Placement, TreeList};
+
+        let mut tree = TreeList::<String>::new();
+        assert_eq!(
+            tree.insert_items(Placement::After, 0, vec!["1".to_string(), "2".to_string()]),
+            Some(0)
+        );
+        assert_eq!(
+            tree.insert_items(
+                Placement::LastChild,
+                0,
+                vec!["3".to_string(), "4".to_string(), "5".to_string()]
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            tree.insert_items(Placement::Before, 2, vec!["6".to_string()]),
+            Some(2)
+        );
+        assert_eq!(tree.insert_items(Placement::After, 0, Vec::new()), None);
+        assert_eq!(
+            tree.insert_items(Placement::Parent, 0, vec!["7".to_string(), "8".to_string()]),
+            None
+        );
+
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 4, 5),
+                (1, false, "3".to_string(), 0, 1),
+                (1, false, "6".to_string(), 0, 1),
+                (1, false, "4".to_string(), 0, 1),
+                (1, false, "5".to_string(), 0, 1),
+                (0, false, "2".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.height(), 6);
+
+        tree.set_collapsed(0, true);
+        assert_eq!(
+            tree.insert_items(
+                Placement::FirstChild,
+                1,
+                vec!["8".to_string(), "9".to_string()]
+            ),
+            None
+        );
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.height(), 2);
+
+        tree.set_collapsed(0, false);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 6, 7),
+                (1, false, "3".to_string(), 2, 3),
+                (2, false, "8".to_string(), 0, 1),
+                (2, false, "9".to_string(), 0, 1),
+                (1, false, "6".to_string(), 0, 1),
+                (1, false, "4".to_string(), 0, 1),
+                (1, false, "5".to_string(), 0, 1),
+                (0, false, "2".to_string(), 0, 1)
+            ]
+        );
+        assert_eq!(tree.height(), 8);
+    }
+
+    #[test]
+    fn test_expand_to() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 2, "4".to_string());
+        tree.insert_item(Placement::After, 3, "5".to_string());
+        tree.insert_item(Placement::After, 0, "6".to_string());
+
+        // Collapse from the innermost level outwards
+        tree.set_collapsed(2, true);
+        tree.set_collapsed(1, true);
+        tree.set_collapsed(0, true);
+        assert_eq!(tree.height(), 2);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, true, "1".to_string(), 4, 1),
+                (0, false, "6".to_string(), 0, 1)
+            ]
+        );
+
+        assert_eq!(tree.expand_to(4), Some(4));
+        assert_eq!(tree.height(), 6);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 4, 5),
+                (1, false, "2".to_string(), 3, 4),
+                (2, false, "3".to_string(), 2, 3),
+                (3, false, "4".to_string(), 0, 1),
+                (3, false, "5".to_string(), 0, 1),
+                (0, false, "6".to_string(), 0, 1)
+            ]
+        );
+
+        // Collapsing and expanding again restores the same heights
+        tree.set_collapsed(2, true);
+        tree.set_collapsed(1, true);
+        tree.set_collapsed(0, true);
+        tree.set_collapsed(2, false);
+        tree.set_collapsed(0, false);
+        assert_eq!(tree.height(), 3);
+        tree.set_collapsed(1, false);
+        assert_eq!(tree.height(), 6);
+
+        assert_eq!(tree.expand_to(10), None);
+    }
+
+    #[test]
+    fn test_item_ids() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::Before, 0, "3".to_string());
+        assert_eq!(tree.next_id(), 3);
+
+        assert_eq!(tree.id_to_item_index(0), Some(1));
+        assert_eq!(tree.id_to_item_index(1), Some(2));
+        assert_eq!(tree.id_to_item_index(2), Some(0));
+
+        tree.remove_with_children(1);
+        assert_eq!(tree.id_to_item_index(0), None);
+        assert_eq!(tree.id_to_item_index(1), None);
+        assert_eq!(tree.id_to_item_index(2), Some(0));
+
+        // Ids are never reused
+        tree.insert_item(Placement::After, 0, "4".to_string());
+        assert_eq!(tree.id_to_item_index(3), Some(1));
+    }
+
+    #[test]
+    fn test_remove_and_insert_subtree() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_container_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::LastChild, 1, "4".to_string());
+        tree.insert_item(Placement::After, 0, "5".to_string());
+        tree.set_collapsed(2, false);
+        tree.set_collapsed(1, true);
+        assert_eq!(tree.height(), 3);
+
+        let nodes = tree.remove_subtree(1).unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(tree.height(), 2);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 0, 1),
+                (0, false, "5".to_string(), 0, 1)
+            ]
+        );
+
+        assert_eq!(tree.insert_subtree(Placement::LastChild, 1, nodes), Some(2));
+        assert_eq!(tree.height(), 3);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 0, 1),
+                (0, false, "5".to_string(), 3, 2),
+                (1, true, "2".to_string(), 2, 1)
+            ]
+        );
+
+        tree.set_collapsed(2, false);
+        assert_eq!(tree.height(), 5);
+        assert!(tree.is_container_item(3));
+        assert_eq!(tree.is_collapsed(3), Some(false));
+
+        assert!(tree.remove_subtree(5).is_none());
+    }
+
+    #[test]
+    fn test_visible_ancestor() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3".to_string());
+        tree.insert_item(Placement::After, 0, "4".to_string());
+
+        assert_eq!(tree.visible_ancestor(2), 2);
+
+        tree.set_collapsed(1, true);
+        assert_eq!(tree.visible_ancestor(2), 1);
+        assert_eq!(tree.visible_ancestor(1), 1);
+
+        tree.set_collapsed(0, true);
+        assert_eq!(tree.visible_ancestor(2), 0);
+        assert_eq!(tree.visible_ancestor(3), 3);
+    }
+
+    #[test]
+    fn test_insert_nth_child() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.insert_item(Placement::NthChild(0), 0, "3".to_string());
+        tree.insert_item(Placement::LastChild, 1, "3a".to_string());
+        tree.insert_item(Placement::NthChild(0), 0, "2".to_string());
+        tree.insert_item(Placement::NthChild(10), 0, "5".to_string());
+        tree.insert_item(Placement::NthChild(2), 0, "4".to_string());
+
+        tree.set_collapsed(2, true);
+        tree.insert_item(Placement::NthChild(1), 0, "2a".to_string());
+
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 6, 6),
+                (1, false, "2".to_string(), 0, 1),
+                (1, false, "2a".to_string(), 0, 1),
+                (1, true, "3".to_string(), 1, 1),
+                (1, false, "4".to_string(), 0, 1),
+                (1, false, "5".to_string(), 0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_move_up_and_down() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.insert_item(Placement::LastChild, 0, "1a".to_string());
+        tree.insert_item(Placement::After, 1, "1b".to_string());
+        tree.insert_item(Placement::After, 0, "2".to_string());
+        tree.insert_item(Placement::LastChild, 3, "2a".to_string());
+        tree.set_collapsed(0, true);
+
+        assert_eq!(tree.move_up(0), Some(0));
+        assert_eq!(tree.move_down(3), Some(3));
+
+        assert_eq!(tree.move_down(0), Some(2));
+        assert_eq!(tree.height(), 3);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "2".to_string(), 1, 2),
+                (1, false, "2a".to_string(), 0, 1),
+                (0, true, "1".to_string(), 2, 1)
+            ]
+        );
+
+        tree.set_collapsed(2, false);
+        assert_eq!(tree.move_up(4), Some(3));
+        assert_eq!(tree.move_up(3), Some(3));
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "2".to_string(), 1, 2),
+                (1, false, "2a".to_string(), 0, 1),
+                (0, false, "1".to_string(), 2, 3),
+                (1, false, "1b".to_string(), 0, 1),
+                (1, false, "1a".to_string(), 0, 1)
+            ]
+        );
+
+        assert_eq!(tree.move_up(2), Some(0));
+        assert_eq!(tree.move_up(5), None);
+    }
+
+    #[test]
+    fn test_retain() {
+        use super::{Placement, RetainPolicy};
+
+        fn build() -> TreeList<String> {
+            let mut tree = TreeList::<String>::new();
+            tree.insert_item(Placement::After, 0, "1".to_string());
+            tree.insert_container_item(Placement::LastChild, 0, "x2".to_string());
+            tree.insert_item(Placement::LastChild, 1, "3".to_string());
+            tree.insert_item(Placement::LastChild, 1, "x4".to_string());
+            tree.insert_item(Placement::LastChild, 3, "5".to_string());
+            tree.insert_item(Placement::After, 0, "x6".to_string());
+            tree
+        }
+
+        let mut tree = build();
+        tree.retain(|item| !item.starts_with('x'), RetainPolicy::RemoveChildren);
+        assert_eq!(tree.height(), 1);
+        assert_eq!(tree.to_vec(), vec![(0, false, "1".to_string(), 0, 1)]);
+
+        // Hidden children of a collapsed container are extracted into it
+        let mut tree = build();
+        tree.set_collapsed(0, true);
+        assert_eq!(tree.height(), 2);
+        tree.retain(|item| !item.starts_with('x'), RetainPolicy::ExtractChildren);
+        assert_eq!(tree.height(), 1);
+        assert_eq!(tree.to_vec(), vec![(0, true, "1".to_string(), 2, 1)]);
+        tree.set_collapsed(0, false);
+        assert_eq!(tree.height(), 3);
+
+        let mut tree = build();
+        tree.set_collapsed(1, false);
+        tree.retain(|item| !item.starts_with('x'), RetainPolicy::ExtractChildren);
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 2, 3),
+                (1, false, "3".to_string(), 0, 1),
+                (1, false, "5".to_string(), 0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_subtree() {
+        use super::Placement;
+
+        let mut tree = TreeList::<usize>::new();
+        tree.insert_item(Placement::After, 0, 0);
+        tree.insert_item(Placement::LastChild, 0, 3);
+        tree.insert_item(Placement::LastChild, 1, 32);
+        tree.insert_item(Placement::LastChild, 1, 31);
+        tree.insert_item(Placement::LastChild, 0, 1);
+        tree.insert_item(Placement::LastChild, 4, 12);
+        tree.insert_item(Placement::LastChild, 4, 13);
+        tree.insert_item(Placement::LastChild, 4, 11);
+        tree.insert_item(Placement::LastChild, 0, 2);
+        tree.set_collapsed(4, true);
+
+        tree.sort_subtree_by(0, &|a: &usize, b: &usize| a.cmp(b));
+        let values: Vec<usize> = tree.items().iter().map(|item| *item.value()).collect();
+        assert_eq!(values, vec![0, 1, 11, 12, 13, 2, 3, 31, 32]);
+        assert_eq!(tree.height(), 6);
+        assert_eq!(tree.is_collapsed(1), Some(true));
+    }
+
+    #[test]
+    fn test_path_to_item_index() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::new();
+        assert_eq!(tree.path_to_item_index(&[0]), None);
+
+        tree.insert_item(Placement::After, 0, "0".to_string());
+        tree.insert_item(Placement::LastChild, 0, "0.0".to_string());
+        tree.insert_item(Placement::LastChild, 0, "0.1".to_string());
+        tree.insert_item(Placement::LastChild, 2, "0.1.0".to_string());
+        tree.insert_item(Placement::After, 0, "1".to_string());
+        tree.set_collapsed(0, true);
+
+        assert_eq!(tree.root_indices(), vec![0, 4]);
+        assert_eq!(tree.path_to_item_index(&[]), None);
+        assert_eq!(tree.path_to_item_index(&[0]), Some(0));
+        assert_eq!(tree.path_to_item_index(&[0, 1, 0]), Some(3));
+        assert_eq!(tree.path_to_item_index(&[1]), Some(4));
+        assert_eq!(tree.path_to_item_index(&[1, 0]), None);
+        assert_eq!(tree.path_to_item_index(&[2]), None);
+    }
+
+    #[test]
+    fn test_from_levels() {
+        let tree = TreeList::from_levels(vec![
+            (0, false, "1".to_string()),
+            (1, true, "2".to_string()),
+            (2, false, "3".to_string()),
+            (1, false, "4".to_string()),
+            (5, false, "5".to_string()),
+            (0, true, "6".to_string()),
+        ]);
+
+        assert_eq!(tree.height(), 5);
+        assert!(tree.is_container_item(0));
+        assert_eq!(
+            tree.to_vec(),
+            vec![
+                (0, false, "1".to_string(), 4, 4),
+                (1, true, "2".to_string(), 1, 1),
+                (1, false, "4".to_string(), 1, 2),
+                (2, false, "5".to_string(), 0, 1),
+                (0, true, "6".to_string(), 0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_state() {
+        use super::CheckState;
+
+        let mut tree = TreeList::<String>::from_levels(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "c".to_string()),
+            (2, false, "d".to_string()),
+            (1, false, "e".to_string()),
+        ]);
+        assert_eq!(tree.check_state(0), Some(CheckState::Unchecked));
+
+        tree.set_checked(2, true);
+        assert_eq!(tree.check_state(0), Some(CheckState::Partial));
+        assert_eq!(tree.check_state(1), Some(CheckState::Partial));
+
+        tree.set_checked(1, true);
+        assert_eq!(tree.check_state(1), Some(CheckState::Checked));
+        assert_eq!(tree.check_state(0), Some(CheckState::Partial));
+
+        tree.set_checked(4, true);
+        assert_eq!(tree.check_state(0), Some(CheckState::Checked));
+
+        tree.set_checked(0, false);
+        assert_eq!(tree.check_state(0), Some(CheckState::Unchecked));
+        assert_eq!(tree.check_state(3), Some(CheckState::Unchecked));
+        assert_eq!(tree.check_state(5), None);
+    }
+
+    #[test]
+    fn test_next_sibling() {
+        let tree = TreeList::<String>::from_levels(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "c".to_string()),
+            (1, false, "d".to_string()),
+            (0, false, "e".to_string()),
+        ]);
+        assert_eq!(tree.next_sibling(0), Some(4));
+        assert_eq!(tree.next_sibling(1), Some(3));
+        assert_eq!(tree.next_sibling(2), None);
+        assert_eq!(tree.next_sibling(3), None);
+        assert_eq!(tree.next_sibling(4), None);
+        assert_eq!(tree.next_sibling(5), None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut tree = TreeList::<String>::from_levels(vec![
+            (0, false, "a".to_string()),
+            (1, false, "b".to_string()),
+            (2, false, "match".to_string()),
+            (1, false, "c".to_string()),
+            (0, true, "d".to_string()),
+            (1, false, "match".to_string()),
+            (0, false, "e".to_string()),
+        ]);
+        tree.set_collapsed(0, true);
+        assert_eq!(tree.height(), 3);
+
+        tree.set_filter(Some(&|value: &String| value == "match"));
+        assert_eq!(tree.height(), 5);
+        assert_eq!(
+            tree.visible_indices().collect::<Vec<_>>(),
+            vec![0, 1, 2, 4, 5]
+        );
+        assert_eq!(tree.row_to_item_index(3), 4);
+        assert_eq!(tree.row_to_item_index(5), 7);
+        assert_eq!(tree.item_index_to_row(5), 4);
+        assert!(!tree.is_visible(3));
+        assert_eq!(tree.visible_ancestor(3), 0);
+
+        tree.set_filter(None);
+        assert_eq!(tree.height(), 3);
+        assert!(tree.get_collapsed(0));
+        assert!(tree.get_collapsed(4));
+    }
+
+    #[test]
+    fn test_remove_beyond_max_depth() {
+        use super::Placement;
+
+        let mut tree = TreeList::<String>::from_levels(vec![
+            (0, false, "a".to_string()),
+            (1, true, "b".to_string()),
+            (2, false, "c".to_string()),
+            (2, false, "d".to_string()),
+        ]);
+        tree.set_max_depth(Some(2));
+        tree.set_collapsed(0, true);
+
+        // The children of the removed item move up into the collapsed parent
+        tree.remove(1);
+        tree.validate();
+        tree.set_collapsed(0, false);
+        assert_eq!(tree.height(), 3);
+
+        tree.insert_container_item(Placement::LastChild, 0, "e".to_string());
+        tree.insert_item(Placement::LastChild, 3, "f".to_string());
+        tree.set_collapsed(0, true);
+        tree.remove_children(3);
+        tree.validate();
+        tree.remove_with_children(3);
+        tree.validate();
+    }
+
+    #[test]
+    fn test_random_operations() {
+        use super::Placement;
+
+        // Simple linear congruential generator, for reproducible sequences
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut random = move |max: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) as usize % max
+        };
+
+        let placements = [
+            Placement::After,
+            Placement::Before,
+            Placement::FirstChild,
+            Placement::LastChild,
+            Placement::NthChild(1),
+            Placement::Parent,
+        ];
+
+        for _ in 0..50 {
+            let mut tree = TreeList::<usize>::new();
+            for step in 0..200 {
+                let index = random(tree.len() + 1);
+                match random(9) {
+                    0 | 1 => {
+                        let placement = placements[random(placements.len())];
+                        tree.insert_item(placement, index, step);
+                    }
+                    2 => {
+                        let placement = placements[random(placements.len())];
+                        tree.insert_container_item(placement, index, step);
+                    }
+                    3 => {
+                        tree.remove(index);
+                    }
+                    4 => {
+                        tree.remove_children(index);
+                    }
+                    5 => {
+                        tree.remove_with_children(index);
+                    }
+                    6 | 7 => {
+                        let collapsed = random(2) == 0;
+                        tree.set_collapsed(index, collapsed);
+                    }
+                    _ => {
+                        let depth = [None, Some(2), Some(3)][random(3)];
+                        tree.set_max_depth(depth);
+                    }
+                }
+                tree.validate();
+            }
+        }
+    }
 }